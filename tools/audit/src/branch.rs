@@ -0,0 +1,421 @@
+use crate::config::BranchConfig;
+use crate::{CheckResult, Severity, Violation};
+use anyhow::{bail, Context, Result};
+use colored::*;
+use regex::Regex;
+use std::collections::HashSet;
+use std::env;
+use std::process::Command;
+
+/// Set by GitHub Actions on pull_request events, where HEAD is detached
+const HEAD_REF_VAR: &str = "GITHUB_HEAD_REF";
+
+const FIELD_SEPARATOR: char = '\x1f';
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A branch name split into its policy parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchName<'a> {
+    pub kind: &'a str,
+    pub ticket: Option<&'a str>,
+    pub description: &'a str,
+}
+
+/// Compiled branch naming policy
+pub struct BranchPolicy {
+    regex: Regex,
+}
+
+impl BranchPolicy {
+    pub fn new(config: &BranchConfig) -> Result<Self> {
+        let types = config
+            .types
+            .iter()
+            .map(|t| regex::escape(t))
+            .collect::<Vec<_>>()
+            .join("|");
+        let pattern = format!(
+            "^(?P<type>{})/(?:(?P<ticket>{})(?:-|$))?(?P<description>{})?$",
+            types, config.ticket_pattern, config.description_pattern
+        );
+        let regex = Regex::new(&pattern).context("Invalid [branch] pattern in config")?;
+        Ok(Self { regex })
+    }
+
+    /// Parse `type/TICKET-123-description`, or None if it breaks the policy
+    pub fn parse<'a>(&self, branch: &'a str) -> Option<BranchName<'a>> {
+        let caps = self.regex.captures(branch)?;
+        let ticket = caps.name("ticket").map(|m| m.as_str());
+        let description = caps.name("description").map_or("", |m| m.as_str());
+        // `fix/` alone, or only a ticket, says nothing about the change
+        if description.is_empty() && ticket.is_none() {
+            return None;
+        }
+        Some(BranchName {
+            kind: caps.name("type")?.as_str(),
+            ticket,
+            description,
+        })
+    }
+}
+
+/// The branch being checked: `--branch`, then `GITHUB_HEAD_REF`, then git
+pub fn current_branch(explicit: Option<&str>) -> Result<String> {
+    let from_env = env::var(HEAD_REF_VAR).ok().filter(|b| !b.is_empty());
+    let branch = match explicit.map(str::to_string).or(from_env) {
+        Some(branch) => branch,
+        None => {
+            let output = Command::new("git")
+                .args(["rev-parse", "--abbrev-ref", "HEAD"])
+                .output()
+                .context("Failed to run git rev-parse")?;
+            String::from_utf8(output.stdout)?.trim().to_string()
+        }
+    };
+    Ok(branch.trim_start_matches("refs/heads/").to_string())
+}
+
+/// Ticket id of the current branch, for checks that require it in trailers
+pub fn branch_ticket(branch: &str, config: &BranchConfig) -> Result<Option<String>> {
+    let policy = BranchPolicy::new(config)?;
+    Ok(policy
+        .parse(branch)
+        .and_then(|name| name.ticket)
+        .map(str::to_string))
+}
+
+pub fn check_branch_name(branch: Option<&str>, config: &BranchConfig) -> Result<CheckResult> {
+    let mut result = CheckResult::new("branch-name");
+    let branch = current_branch(branch)?;
+
+    // A detached HEAD outside CI has no branch to check
+    if branch == "HEAD" || config.exempt.contains(&branch) {
+        return Ok(result);
+    }
+
+    let policy = BranchPolicy::new(config)?;
+    let message = match policy.parse(&branch) {
+        Some(name) => match name.ticket {
+            Some(ticket) => {
+                println!("🎫 Ticket: {}", ticket);
+                None
+            }
+            None if config.require_ticket => Some(format!(
+                "Branch name has no ticket id (e.g. {}/PROJ-123-{}): {}",
+                name.kind, name.description, branch
+            )),
+            None => None,
+        },
+        None => Some(format!("Invalid branch name: {}", branch)),
+    };
+
+    if let Some(message) = message {
+        println!("{}", "❌ Invalid branch name!".red());
+        println!("Expected format: type/description or type/TICKET-123-description");
+        println!("Types: {}", config.types.join(", "));
+        result.add_violation(Violation {
+            file: String::new(),
+            line: None,
+            message,
+            rule: Some("branch-name".to_string()),
+            ..Default::default()
+        });
+    }
+
+    Ok(result)
+}
+
+/// A local or remote-tracking branch from `git for-each-ref`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchRef {
+    /// `feature/x` or `origin/feature/x`
+    pub name: String,
+    pub remote: bool,
+    /// Unix time of the last commit
+    pub committed: i64,
+}
+
+impl BranchRef {
+    /// Name without the remote, as checked against the naming policy
+    fn branch(&self) -> &str {
+        match self.remote {
+            true => self.name.split_once('/').map_or(&self.name, |(_, b)| b),
+            false => &self.name,
+        }
+    }
+}
+
+/// Why a branch shows up in the report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchStatus {
+    Merged,
+    Inactive { days: i64 },
+    InvalidName,
+}
+
+fn list_refs(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("for-each-ref")
+        .args(args)
+        .output()
+        .context("Failed to run git for-each-ref")?;
+    if !output.status.success() {
+        bail!(
+            "git for-each-ref failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_refs(output: &str) -> Vec<BranchRef> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(FIELD_SEPARATOR);
+            let refname = fields.next()?;
+            let name = fields.next()?;
+            let committed = fields.next()?.parse().ok()?;
+            // `origin/HEAD` is a pointer, not a branch
+            if refname.ends_with("/HEAD") {
+                return None;
+            }
+            Some(BranchRef {
+                name: name.to_string(),
+                remote: refname.starts_with("refs/remotes/"),
+                committed,
+            })
+        })
+        .collect()
+}
+
+/// Classify branches as merged, inactive or badly named
+pub fn classify_branches(
+    refs: &[BranchRef],
+    merged: &HashSet<String>,
+    now: i64,
+    stale_days: u64,
+    base: &str,
+    config: &BranchConfig,
+) -> Result<Vec<(BranchRef, Vec<BranchStatus>)>> {
+    let policy = BranchPolicy::new(config)?;
+    let mut report = vec![];
+    for branch in refs {
+        let name = branch.branch();
+        if name == base || config.exempt.iter().any(|e| e == name) {
+            continue;
+        }
+        let mut statuses = vec![];
+        if merged.contains(&branch.name) {
+            statuses.push(BranchStatus::Merged);
+        }
+        let days = (now - branch.committed) / SECONDS_PER_DAY;
+        if days > stale_days as i64 {
+            statuses.push(BranchStatus::Inactive { days });
+        }
+        if policy.parse(name).is_none() {
+            statuses.push(BranchStatus::InvalidName);
+        }
+        if !statuses.is_empty() {
+            report.push((branch.clone(), statuses));
+        }
+    }
+    Ok(report)
+}
+
+/// Report merged, inactive and badly named branches, optionally deleting
+/// merged local branches
+pub fn check_branches(
+    stale_days: Option<u64>,
+    base: Option<&str>,
+    prune: bool,
+    config: &BranchConfig,
+) -> Result<CheckResult> {
+    let mut result = CheckResult::new("branches");
+    let stale_days = stale_days.unwrap_or(config.stale_days);
+    let base = base.unwrap_or(&config.base);
+
+    let format = format!(
+        "--format=%(refname){f}%(refname:short){f}%(committerdate:unix)",
+        f = FIELD_SEPARATOR
+    );
+    let refs = parse_refs(&list_refs(&[&format, "refs/heads", "refs/remotes"])?);
+    let merged: HashSet<String> = list_refs(&[
+        "--format=%(refname:short)",
+        &format!("--merged={}", base),
+        "refs/heads",
+        "refs/remotes",
+    ])
+    .with_context(|| format!("Cannot check merges into '{}' ([branch].base)", base))?
+    .lines()
+    .map(str::to_string)
+    .collect();
+
+    let now = chrono::Utc::now().timestamp();
+    let report = classify_branches(&refs, &merged, now, stale_days, base, config)?;
+    let current = current_branch(None).unwrap_or_default();
+
+    for (branch, statuses) in &report {
+        let labels: Vec<String> = statuses
+            .iter()
+            .map(|s| match s {
+                BranchStatus::Merged => format!("merged into {}", base),
+                BranchStatus::Inactive { days } => format!("inactive for {} days", days),
+                BranchStatus::InvalidName => "invalid name".to_string(),
+            })
+            .collect();
+        let icon = if statuses.contains(&BranchStatus::InvalidName) {
+            "❌"
+        } else {
+            "🧹"
+        };
+        println!("{} {} ({})", icon, branch.name, labels.join(", "));
+
+        for status in statuses {
+            let (rule, severity, message) = match status {
+                BranchStatus::InvalidName => (
+                    "branch-name",
+                    Severity::Medium,
+                    format!("Invalid branch name: {}", branch.branch()),
+                ),
+                BranchStatus::Merged => (
+                    "branch-merged",
+                    Severity::Info,
+                    format!("Merged into {}", base),
+                ),
+                BranchStatus::Inactive { days } => (
+                    "branch-inactive",
+                    Severity::Info,
+                    format!("No commits for {} days", days),
+                ),
+            };
+            result.add_violation(Violation {
+                file: branch.name.clone(),
+                line: None,
+                message,
+                rule: Some(rule.to_string()),
+                severity: Some(severity),
+                ..Default::default()
+            });
+        }
+
+        if prune && !branch.remote && statuses.contains(&BranchStatus::Merged) {
+            if branch.name == current {
+                println!("   Skipped deleting the checked out branch");
+                continue;
+            }
+            let deleted = Command::new("git")
+                .args(["branch", "-d", &branch.name])
+                .output()
+                .context("Failed to run git branch -d")?;
+            if deleted.status.success() {
+                println!("   {}", "Deleted".green());
+            } else {
+                println!(
+                    "   {} {}",
+                    "Failed to delete:".red(),
+                    String::from_utf8_lossy(&deleted.stderr).trim()
+                );
+            }
+        }
+    }
+
+    if report.is_empty() {
+        println!(
+            "{}",
+            "✅ All branches are active, unmerged and well named".green()
+        );
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_branch_names() {
+        let policy = BranchPolicy::new(&BranchConfig::default()).unwrap();
+        assert_eq!(
+            policy.parse("fix/PROJ-123-login-redirect"),
+            Some(BranchName {
+                kind: "fix",
+                ticket: Some("PROJ-123"),
+                description: "login-redirect",
+            })
+        );
+        assert_eq!(policy.parse("hotfix/OPS-7").unwrap().ticket, Some("OPS-7"));
+        assert_eq!(
+            policy.parse("feature/add-monitor-groups").unwrap().ticket,
+            None
+        );
+        assert!(policy.parse("feature/").is_none());
+        assert!(policy.parse("wip/add-monitor-groups").is_none());
+        assert!(policy.parse("feature/add_monitor").is_none());
+    }
+
+    #[test]
+    fn test_classify_branches() {
+        let day = SECONDS_PER_DAY;
+        let output = [
+            "refs/heads/main\x1fmain\x1f1000",
+            "refs/heads/feature/add-groups\x1ffeature/add-groups\x1f1000",
+            "refs/heads/old_stuff\x1fold_stuff\x1f1000",
+            "refs/remotes/origin/HEAD\x1forigin\x1f1000",
+            "refs/remotes/origin/fix/PROJ-1-login\x1forigin/fix/PROJ-1-login\x1f0",
+        ]
+        .join("\n");
+        let refs = parse_refs(&output);
+        assert_eq!(refs.len(), 4);
+        assert!(refs[3].remote);
+
+        let merged = HashSet::from(["feature/add-groups".to_string()]);
+        let report = classify_branches(
+            &refs,
+            &merged,
+            1000 + 10 * day,
+            30,
+            "main",
+            &BranchConfig::default(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|(b, s)| (b.name, s))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            report,
+            vec![
+                ("feature/add-groups".to_string(), vec![BranchStatus::Merged]),
+                ("old_stuff".to_string(), vec![BranchStatus::InvalidName]),
+            ]
+        );
+
+        let report = classify_branches(
+            &refs,
+            &merged,
+            40 * day,
+            30,
+            "main",
+            &BranchConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(report[2].0.name, "origin/fix/PROJ-1-login");
+        assert_eq!(report[2].1, vec![BranchStatus::Inactive { days: 40 }]);
+    }
+
+    #[test]
+    fn test_branch_ticket_with_custom_pattern() {
+        let config = BranchConfig {
+            types: vec!["feat".to_string()],
+            ticket_pattern: "#[0-9]+".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            branch_ticket("feat/#42-status-badges", &config).unwrap(),
+            Some("#42".to_string())
+        );
+        assert_eq!(current_branch(Some("refs/heads/fix/x")).unwrap(), "fix/x");
+    }
+}
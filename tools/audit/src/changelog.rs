@@ -0,0 +1,391 @@
+use crate::commits::{self, LoggedCommit};
+use crate::config::ChangelogConfig;
+use crate::CheckResult;
+use anyhow::{Context, Result};
+use colored::*;
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// `BREAKING CHANGE: description` footers, as written by `check_commit_msg`
+const BREAKING_FOOTER_PATTERN: &str = r"^BREAKING[ -]CHANGE: (.+)$";
+
+/// `Refs: #12, PROJ-34` style trailers
+const TRAILER_PATTERN: &str = r"^(Refs|Closes|Fixes|Resolves): (.+)$";
+
+/// Title for changelogs created from scratch
+const CHANGELOG_TITLE: &str = "# Changelog";
+
+/// One changelog line, taken from a conventional commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub subject: String,
+    pub sha: String,
+    /// `(verb, reference)` pairs from trailers, e.g. `("closes", "#12")`
+    pub references: Vec<(String, String)>,
+    /// Breaking change description from the footer, or the subject for `!`
+    pub breaking: Option<String>,
+}
+
+/// Parse a commit with the commit-msg grammar; None for merges and free-form messages
+pub fn parse_entry(commit: &LoggedCommit) -> Option<Entry> {
+    let mut lines = commit.message.lines();
+    let subject = lines.next()?.trim();
+    if commits::is_merge_subject(subject) {
+        return None;
+    }
+
+    let (kind, scope, text, marked_breaking) = match commits::git_revert_subject(subject) {
+        Some(reverted) => ("revert".to_string(), None, reverted.to_string(), false),
+        None => {
+            let header = commits::parse_header(subject)?;
+            let scope = (!header.scopes.is_empty()).then(|| header.scopes.join(", "));
+            (
+                header.kind.to_string(),
+                scope,
+                header.subject.to_string(),
+                header.breaking,
+            )
+        }
+    };
+
+    static BREAKING_FOOTER: OnceLock<Regex> = OnceLock::new();
+    static TRAILER: OnceLock<Regex> = OnceLock::new();
+    let breaking_footer =
+        BREAKING_FOOTER.get_or_init(|| Regex::new(BREAKING_FOOTER_PATTERN).unwrap());
+    let trailer = TRAILER.get_or_init(|| Regex::new(TRAILER_PATTERN).unwrap());
+    let mut breaking = None;
+    let mut references = vec![];
+    let mut in_breaking = false;
+    for line in lines {
+        let line = line.trim_end();
+        if let Some(caps) = breaking_footer.captures(line) {
+            breaking = Some(caps[1].to_string());
+            in_breaking = true;
+        } else if let Some(caps) = trailer.captures(line) {
+            in_breaking = false;
+            let verb = caps[1].to_lowercase();
+            for reference in caps[2].split(',').map(str::trim).filter(|r| !r.is_empty()) {
+                references.push((verb.clone(), reference.to_string()));
+            }
+        } else if line.is_empty() {
+            in_breaking = false;
+        } else if in_breaking {
+            // Footer descriptions may wrap onto following lines
+            if let Some(text) = breaking.as_mut() {
+                text.push(' ');
+                text.push_str(line.trim());
+            }
+        }
+    }
+    if marked_breaking && breaking.is_none() {
+        breaking = Some(text.clone());
+    }
+
+    Some(Entry {
+        kind,
+        scope,
+        subject: text,
+        sha: commit.sha.clone(),
+        references,
+        breaking,
+    })
+}
+
+/// Compiled tracker patterns for issue links
+struct Linker<'a> {
+    trackers: Vec<(Regex, &'a str)>,
+    commit_url: Option<&'a str>,
+}
+
+impl<'a> Linker<'a> {
+    fn new(config: &'a ChangelogConfig) -> Result<Self> {
+        let trackers = config
+            .trackers
+            .iter()
+            .map(|t| {
+                Regex::new(&t.pattern)
+                    .with_context(|| format!("Invalid tracker pattern '{}'", t.pattern))
+                    .map(|regex| (regex, t.url.as_str()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            trackers,
+            commit_url: config.commit_url.as_deref(),
+        })
+    }
+
+    /// Turn tracker references in text into Markdown links
+    fn references(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (regex, url) in &self.trackers {
+            text = regex
+                .replace_all(&text, |caps: &regex::Captures| {
+                    let id = caps.get(1).unwrap_or_else(|| caps.get(0).unwrap());
+                    format!("[{}]({})", &caps[0], url.replace("{id}", id.as_str()))
+                })
+                .into_owned();
+        }
+        text
+    }
+
+    fn commit(&self, sha: &str) -> String {
+        let short = &sha[..sha.len().min(7)];
+        match self.commit_url {
+            Some(url) => format!("[{}]({})", short, url.replace("{sha}", sha)),
+            None => short.to_string(),
+        }
+    }
+
+    fn entry(&self, entry: &Entry, text: &str) -> String {
+        let mut line = String::from("- ");
+        if let Some(scope) = &entry.scope {
+            line.push_str(&format!("**{}:** ", scope));
+        }
+        line.push_str(&self.references(text));
+        line.push_str(&format!(" ({})", self.commit(&entry.sha)));
+        for (verb, reference) in &entry.references {
+            line.push_str(&format!(", {} {}", verb, self.references(reference)));
+        }
+        line
+    }
+}
+
+/// Render a Markdown changelog section for the given commits
+fn render(heading: &str, commits: &[LoggedCommit], config: &ChangelogConfig) -> Result<String> {
+    let linker = Linker::new(config)?;
+    let entries: Vec<Entry> = commits.iter().filter_map(parse_entry).collect();
+    let mut out = format!("## {}\n", heading);
+
+    let breaking: Vec<&Entry> = entries.iter().filter(|e| e.breaking.is_some()).collect();
+    if !breaking.is_empty() {
+        out.push_str("\n### ⚠ BREAKING CHANGES\n\n");
+        for entry in breaking {
+            let text = entry.breaking.as_deref().unwrap_or_default();
+            out.push_str(&linker.entry(entry, text));
+            out.push('\n');
+        }
+    }
+
+    for section in &config.sections {
+        let mut group: Vec<&Entry> = entries.iter().filter(|e| e.kind == section.kind).collect();
+        if group.is_empty() {
+            continue;
+        }
+        // Scoped entries grouped alphabetically, unscoped ones last
+        group.sort_by_key(|e| (e.scope.is_none(), e.scope.clone()));
+        out.push_str(&format!("\n### {}\n\n", section.title));
+        for entry in group {
+            out.push_str(&linker.entry(entry, &entry.subject));
+            out.push('\n');
+        }
+    }
+
+    Ok(out)
+}
+
+/// Insert a section above the newest one, replacing a section with the same version
+fn prepend(existing: &str, section: &str, version: &str) -> String {
+    let Some(start) = section_start(existing, 0) else {
+        let preamble = existing.trim_end();
+        let preamble = if preamble.is_empty() {
+            CHANGELOG_TITLE
+        } else {
+            preamble
+        };
+        return format!("{}\n\n{}", preamble, section);
+    };
+
+    let first = &existing[start..];
+    let heading = first.lines().next().unwrap_or_default();
+    let rest = if heading == format!("## {}", version)
+        || heading.starts_with(&format!("## {} ", version))
+    {
+        section_start(existing, start + heading.len()).map_or("", |end| &existing[end..])
+    } else {
+        first
+    };
+
+    let mut out = format!("{}{}", &existing[..start], section);
+    if !rest.is_empty() {
+        out.push('\n');
+        out.push_str(rest);
+    }
+    out
+}
+
+/// Byte offset of the next `## ` heading at or after `from`
+fn section_start(text: &str, from: usize) -> Option<usize> {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if offset >= from && line.starts_with("## ") {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// The most recent tag reachable from `rev`, optionally matching a glob
+pub fn latest_tag(rev: &str, pattern: Option<&str>) -> Option<String> {
+    let mut args = vec!["describe", "--tags", "--abbrev=0"];
+    if let Some(pattern) = pattern {
+        args.extend(["--match", pattern]);
+    }
+    args.push(rev);
+    let output = Command::new("git").args(&args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|tag| !tag.is_empty())
+}
+
+/// Render the changelog for `from..to`, printing it or prepending it to the file
+pub fn generate_changelog(
+    from: Option<&str>,
+    to: &str,
+    version: &str,
+    write: bool,
+    config: &ChangelogConfig,
+) -> Result<CheckResult> {
+    let result = CheckResult::new("changelog");
+
+    // Start after the previous tag; `to` itself may be tagged already, e.g.
+    // right after `audit release --tag`
+    let from = from
+        .map(str::to_string)
+        .or_else(|| latest_tag(&format!("{}^", to), None));
+    let range = match &from {
+        Some(from) => format!("{}..{}", from, to),
+        None => to.to_string(),
+    };
+    let commits = commits::log_commits(&[&range], true)?;
+
+    let date = chrono::Local::now().format("%Y-%m-%d");
+    let heading = format!("{} ({})", version, date);
+    let section = render(&heading, &commits, config)?;
+
+    if !write {
+        print!("{}", section);
+        return Ok(result);
+    }
+
+    let path = Path::new(&config.file);
+    let existing = if path.exists() {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", config.file))?
+    } else {
+        String::new()
+    };
+    fs::write(path, prepend(&existing, &section, version))
+        .with_context(|| format!("Failed to write {}", config.file))?;
+    println!(
+        "{}",
+        format!(
+            "✅ Added {} ({} commits in {}) to {}",
+            version,
+            commits.len(),
+            range,
+            config.file
+        )
+        .green()
+    );
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TrackerConfig;
+
+    fn commit(sha: &str, message: &str) -> LoggedCommit {
+        LoggedCommit {
+            sha: sha.to_string(),
+            author: "Ada <ada@example.com>".to_string(),
+            date: "2024-05-01T10:00:00+00:00".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn config() -> ChangelogConfig {
+        ChangelogConfig {
+            commit_url: Some("https://github.com/o/r/commit/{sha}".to_string()),
+            trackers: vec![
+                TrackerConfig {
+                    pattern: r"#(\d+)".to_string(),
+                    url: "https://github.com/o/r/issues/{id}".to_string(),
+                },
+                TrackerConfig {
+                    pattern: r"\bPROJ-\d+\b".to_string(),
+                    url: "https://jira.example.com/browse/{id}".to_string(),
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_groups_types_and_scopes() {
+        let commits = vec![
+            commit("aaaaaaa1", "feat(monitors): add SSL checks (#12)"),
+            commit("bbbbbbb2", "fix: handle timeouts\n\nCloses: PROJ-7"),
+            commit(
+                "ccccccc3",
+                "feat(auth)!: drop legacy sessions\n\nBREAKING CHANGE: users must\nsign in again",
+            ),
+            commit("ddddddd4", "chore: bump deps"),
+            commit("eeeeeee5", "update readme"),
+            commit(
+                "fffffff6",
+                "Revert \"feat(cron): run every minute\"\n\nThis reverts commit 1234567.",
+            ),
+        ];
+        let section = render("1.2.0 (2024-05-02)", &commits, &config()).unwrap();
+        assert_eq!(
+            section,
+            "## 1.2.0 (2024-05-02)
+
+### ⚠ BREAKING CHANGES
+
+- **auth:** users must sign in again ([ccccccc](https://github.com/o/r/commit/ccccccc3))
+
+### Features
+
+- **auth:** drop legacy sessions ([ccccccc](https://github.com/o/r/commit/ccccccc3))
+- **monitors:** add SSL checks ([#12](https://github.com/o/r/issues/12)) ([aaaaaaa](https://github.com/o/r/commit/aaaaaaa1))
+
+### Bug Fixes
+
+- handle timeouts ([bbbbbbb](https://github.com/o/r/commit/bbbbbbb2)), closes [PROJ-7](https://jira.example.com/browse/PROJ-7)
+
+### Reverts
+
+- feat(cron): run every minute ([fffffff](https://github.com/o/r/commit/fffffff6))
+"
+        );
+    }
+
+    #[test]
+    fn test_prepend_replaces_same_version() {
+        let section = "## Unreleased (2024-05-02)\n\n### Features\n\n- b\n";
+        assert_eq!(
+            prepend("", section, "Unreleased"),
+            format!("# Changelog\n\n{}", section)
+        );
+
+        let existing = "# Changelog\n\nAll notable changes.\n\n## Unreleased (2024-05-01)\n\n- a\n\n## 1.0.0 (2024-04-01)\n\n- first\n";
+        assert_eq!(
+            prepend(existing, section, "Unreleased"),
+            format!(
+                "# Changelog\n\nAll notable changes.\n\n{}\n## 1.0.0 (2024-04-01)\n\n- first\n",
+                section
+            )
+        );
+        assert!(prepend(existing, section, "1.1.0").contains("- b\n\n## Unreleased (2024-05-01)"));
+    }
+}
@@ -0,0 +1,967 @@
+use crate::branch;
+use crate::config::{BranchConfig, CommitsConfig};
+use crate::{CheckResult, Violation};
+use anyhow::{bail, Context, Result};
+use colored::*;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// `type(scope)!: subject`, with an optional `revert: ` prefix
+const HEADER_PATTERN: &str = r"^(?:revert: )?(?P<type>[a-zA-Z]+)(?:\((?P<scope>[^()]*)\))?(?P<breaking>!)?: (?P<subject>.+)$";
+
+/// Maximum edit distance for "did you mean" suggestions
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// Rules that only run when enabled in `[commits.rules]`
+const OPT_IN_RULES: &[&str] = &["breaking-change-footer", "no-fixup", "ticket-trailer"];
+
+/// Subject prefixes of commits meant to be squashed by `git rebase --autosquash`
+const FIXUP_PREFIXES: &[&str] = &["fixup! ", "squash! ", "amend! "];
+
+/// The subject `git revert` generates
+const GIT_REVERT_PATTERN: &str = r#"^Revert ".+"$"#;
+
+/// The body line `git revert` generates
+const REVERTS_COMMIT_PATTERN: &str = r"This reverts commit [0-9a-f]{7,40}";
+
+/// Anything that looks like an attempt at a breaking change footer
+const BREAKING_LIKE_PATTERN: &str = r"(?i)^breaking[ _-]?changes?\b";
+
+/// A correctly formatted breaking change footer
+const BREAKING_FOOTER_PATTERN: &str = r"^BREAKING[ -]CHANGE: \S";
+
+/// Anything that looks like an issue reference trailer
+const TRAILER_LIKE_PATTERN: &str =
+    r"(?i)^(refs?|closes?|closed|fix(es|ed)?|resolves?|resolved)\b\s*:?\s*(#|[A-Z]+-\d|https?://)";
+
+/// `Refs: #12, PROJ-34, https://...`
+const TRAILER_PATTERN: &str = r"^(Refs|Closes|Fixes|Resolves): (#\d+|[A-Z][A-Z0-9]+-\d+|https?://\S+)(, (#\d+|[A-Z][A-Z0-9]+-\d+|https?://\S+))*$";
+
+/// Git's `--cleanup=scissors` marker; everything below is dropped
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// Separates commits and fields in `git log` output
+const COMMIT_SEPARATOR: char = '\x1e';
+const FIELD_SEPARATOR: char = '\x1f';
+
+/// A parsed conventional commit header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header<'a> {
+    pub kind: &'a str,
+    pub scopes: Vec<&'a str>,
+    pub breaking: bool,
+    pub subject: &'a str,
+}
+
+/// Parse `type(scope, scope)!: subject`
+pub fn parse_header(line: &str) -> Option<Header<'_>> {
+    static HEADER: OnceLock<Regex> = OnceLock::new();
+    let regex = HEADER.get_or_init(|| Regex::new(HEADER_PATTERN).unwrap());
+    let caps = regex.captures(line)?;
+    Some(Header {
+        kind: caps.name("type")?.as_str(),
+        scopes: caps
+            .name("scope")
+            .map(|s| s.as_str().split(',').map(str::trim).collect())
+            .unwrap_or_default(),
+        breaking: caps.name("breaking").is_some(),
+        subject: caps.name("subject")?.as_str(),
+    })
+}
+
+/// A commit message rule violation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub line: usize,
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl Issue {
+    fn new(line: usize, rule: &'static str, message: String) -> Self {
+        Self {
+            line,
+            rule,
+            message,
+        }
+    }
+}
+
+/// The subject `git revert` generates, returning the reverted subject
+pub fn git_revert_subject(subject: &str) -> Option<&str> {
+    static GIT_REVERT: OnceLock<Regex> = OnceLock::new();
+    GIT_REVERT
+        .get_or_init(|| Regex::new(GIT_REVERT_PATTERN).unwrap())
+        .is_match(subject)
+        .then(|| &subject["Revert \"".len()..subject.len() - 1])
+}
+
+/// Merge commits generated by git and GitHub are never linted
+pub fn is_merge_subject(subject: &str) -> bool {
+    subject.starts_with("Merge branch")
+        || subject.starts_with("Merge pull request")
+        || subject.starts_with("Merge remote-tracking branch")
+}
+
+/// Whether a rule is enabled, honouring `[commits.rules]` overrides
+pub fn rule_enabled(config: &CommitsConfig, rule: &str) -> bool {
+    config
+        .rules
+        .get(rule)
+        .copied()
+        .unwrap_or(!OPT_IN_RULES.contains(&rule))
+}
+
+/// Message lines without leading and trailing blank lines, with their
+/// 1-based line numbers
+fn cleaned_lines(message: &str) -> Vec<(usize, &str)> {
+    let mut lines: Vec<(usize, &str)> = message
+        .lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l.trim_end()))
+        .skip_while(|(_, l)| l.is_empty())
+        .collect();
+    while lines.last().is_some_and(|(_, l)| l.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Read a commit message file the way git cleans it up on commit
+///
+/// Comment lines and everything below the scissors line are dropped. Only
+/// message files have comments: in stored messages (`git log %B`) a line
+/// like `#12 fixed by ...` is content.
+fn read_message_file(file: &str) -> Result<String> {
+    let msg = fs::read_to_string(file).context("Failed to read commit message file")?;
+    Ok(strip_comments(&msg))
+}
+
+fn strip_comments(message: &str) -> String {
+    message
+        .lines()
+        .take_while(|l| *l != SCISSORS)
+        .filter(|l| !l.starts_with('#'))
+        .map(|l| format!("{}\n", l))
+        .collect()
+}
+
+/// Lint a commit message against the configured policy
+pub fn lint_message(message: &str, config: &CommitsConfig) -> Vec<Issue> {
+    let mut issues = vec![];
+    let mut push = |rule: &'static str, line: usize, message: String| {
+        if rule_enabled(config, rule) {
+            issues.push(Issue::new(line, rule, message));
+        }
+    };
+
+    let lines = cleaned_lines(message);
+    let Some(&(subject_line, subject)) = lines.first() else {
+        push("header-format", 1, "Commit message is empty".to_string());
+        return issues;
+    };
+    let body = &lines[1..];
+
+    if is_merge_subject(subject) {
+        return issues;
+    }
+
+    // Fixups are squashed away before merging; only reject them on request
+    if let Some(prefix) = FIXUP_PREFIXES.iter().find(|p| subject.starts_with(*p)) {
+        push(
+            "no-fixup",
+            subject_line,
+            format!(
+                "'{}' commits must be squashed before merging",
+                prefix.trim()
+            ),
+        );
+        return issues;
+    }
+
+    let length = subject.chars().count();
+    if length > config.subject_max_length {
+        push(
+            "subject-max-length",
+            subject_line,
+            format!(
+                "Commit subject is {} characters (max {})",
+                length, config.subject_max_length
+            ),
+        );
+    }
+
+    if body.first().is_some_and(|(_, l)| !l.is_empty()) {
+        push(
+            "body-leading-blank",
+            body[0].0,
+            "Separate the subject from the body with a blank line".to_string(),
+        );
+    }
+
+    for &(line, text) in body {
+        let length = text.chars().count();
+        // Unbreakable lines such as URLs cannot be wrapped
+        if length > config.body_max_line_length && text.trim().contains(' ') {
+            push(
+                "body-max-line-length",
+                line,
+                format!(
+                    "Body line is {} characters (wrap at {})",
+                    length, config.body_max_line_length
+                ),
+            );
+        }
+    }
+
+    let breaking_footer = lint_footers(body, &mut push);
+
+    if git_revert_subject(subject).is_some() {
+        static REVERTS: OnceLock<Regex> = OnceLock::new();
+        let reverts = REVERTS.get_or_init(|| Regex::new(REVERTS_COMMIT_PATTERN).unwrap());
+        if !body.iter().any(|(_, l)| reverts.is_match(l)) {
+            push(
+                "revert-format",
+                subject_line,
+                "Revert commits must say 'This reverts commit <sha>.' in the body".to_string(),
+            );
+        }
+        return issues;
+    }
+
+    let Some(header) = parse_header(subject) else {
+        push(
+            "header-format",
+            subject_line,
+            format!("Invalid commit message: {}", subject),
+        );
+        return issues;
+    };
+
+    if !config.types.iter().any(|t| t == header.kind) {
+        push(
+            "type-enum",
+            subject_line,
+            unknown("type", header.kind, &config.types),
+        );
+    }
+
+    if !config.scopes.is_empty() {
+        for scope in &header.scopes {
+            if !config.scopes.iter().any(|s| s == scope) {
+                push(
+                    "scope-enum",
+                    subject_line,
+                    unknown("scope", scope, &config.scopes),
+                );
+            }
+        }
+    }
+
+    if header.subject.ends_with('.') {
+        push(
+            "subject-full-stop",
+            subject_line,
+            "Commit subject must not end with a period".to_string(),
+        );
+    }
+
+    if header.breaking && !breaking_footer {
+        push(
+            "breaking-change-footer",
+            subject_line,
+            "Breaking changes marked with '!' need a 'BREAKING CHANGE: ...' footer".to_string(),
+        );
+    }
+
+    issues
+}
+
+/// Require the branch's ticket id in a `Refs:`/`Closes:` style trailer
+pub fn lint_ticket(message: &str, ticket: &str, config: &CommitsConfig) -> Option<Issue> {
+    if !rule_enabled(config, "ticket-trailer") {
+        return None;
+    }
+    let lines = cleaned_lines(message);
+    let &(_, subject) = lines.first()?;
+    if is_merge_subject(subject) || FIXUP_PREFIXES.iter().any(|p| subject.starts_with(p)) {
+        return None;
+    }
+    if lines
+        .iter()
+        .any(|(_, l)| trailer_regex().is_match(l) && l.split([' ', ',']).any(|r| r == ticket))
+    {
+        return None;
+    }
+    let line = lines.last().map_or(1, |(line, _)| *line);
+    Some(Issue::new(
+        line,
+        "ticket-trailer",
+        format!(
+            "Reference the branch ticket with a 'Refs: {}' trailer",
+            ticket
+        ),
+    ))
+}
+
+/// Staged files shown in path issues before eliding the rest
+const MAX_LISTED_PATHS: usize = 3;
+
+/// Paths staged for the next commit
+pub fn staged_files() -> Result<Vec<String>> {
+    git_paths(&["diff", "--cached", "--name-only", "-z"])
+}
+
+/// Paths a commit changes relative to its first parent; empty for merges
+pub fn commit_files(sha: &str) -> Result<Vec<String>> {
+    git_paths(&[
+        "diff-tree",
+        "--no-commit-id",
+        "-r",
+        "--root",
+        "--name-only",
+        "-z",
+        sha,
+    ])
+}
+
+fn git_paths(args: &[&str]) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run git {}", args[0]))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Compile path globs from `.audit.toml`
+pub fn path_set(globs: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).with_context(|| format!("Invalid glob '{}'", glob))?);
+    }
+    Ok(builder.build()?)
+}
+
+fn list_paths(paths: &[&String]) -> String {
+    let mut listed = paths
+        .iter()
+        .take(MAX_LISTED_PATHS)
+        .map(|p| p.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if paths.len() > MAX_LISTED_PATHS {
+        listed.push_str(&format!(" and {} more", paths.len() - MAX_LISTED_PATHS));
+    }
+    listed
+}
+
+/// Compare the declared type and scope with the staged files
+///
+/// `type-paths` checks `[commits.type_paths]` (docs commits touching only
+/// docs, test commits touching tests); `scope-paths` requires the scopes in
+/// `[commits.required_scopes]` when their `scope_paths` match, unless the
+/// configured trailer is present.
+pub fn lint_paths(message: &str, paths: &[String], config: &CommitsConfig) -> Result<Vec<Issue>> {
+    let mut issues = vec![];
+    let lines = cleaned_lines(message);
+    let Some(&(subject_line, subject)) = lines.first() else {
+        return Ok(issues);
+    };
+    let Some(header) = parse_header(subject) else {
+        return Ok(issues);
+    };
+    if paths.is_empty() {
+        return Ok(issues);
+    }
+
+    if let Some(rule) = config
+        .type_paths
+        .get(header.kind)
+        .filter(|_| rule_enabled(config, "type-paths"))
+    {
+        let only = path_set(&rule.only)?;
+        let outside: Vec<&String> = paths.iter().filter(|p| !only.is_match(p)).collect();
+        if !rule.only.is_empty() && !outside.is_empty() {
+            issues.push(Issue::new(
+                subject_line,
+                "type-paths",
+                format!(
+                    "'{}' commits should only touch {}, but also change {}",
+                    header.kind,
+                    rule.only.join(", "),
+                    list_paths(&outside)
+                ),
+            ));
+        }
+        let any = path_set(&rule.any)?;
+        if !rule.any.is_empty() && !paths.iter().any(|p| any.is_match(p)) {
+            issues.push(Issue::new(
+                subject_line,
+                "type-paths",
+                format!(
+                    "'{}' commits should change files matching {}",
+                    header.kind,
+                    rule.any.join(", ")
+                ),
+            ));
+        }
+    }
+
+    if rule_enabled(config, "scope-paths") {
+        for (scope, trailer) in &config.required_scopes {
+            if header.scopes.contains(&scope.as_str()) {
+                continue;
+            }
+            let prefix = format!("{}: ", trailer);
+            if !trailer.is_empty() && lines.iter().any(|(_, l)| l.starts_with(&prefix)) {
+                continue;
+            }
+            let globs = config.scope_paths.get(scope).cloned().unwrap_or_default();
+            let set = path_set(&globs)?;
+            let matched: Vec<&String> = paths.iter().filter(|p| set.is_match(p)).collect();
+            if matched.is_empty() {
+                continue;
+            }
+            let alternative = if trailer.is_empty() {
+                String::new()
+            } else {
+                format!(" or a '{}:' trailer", trailer)
+            };
+            issues.push(Issue::new(
+                subject_line,
+                "scope-paths",
+                format!(
+                    "Changes to {} need the '{}' scope{}",
+                    list_paths(&matched),
+                    scope,
+                    alternative
+                ),
+            ));
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Ticket id from the branch name, when the ticket-trailer rule needs it
+///
+/// `branch` defaults to the current branch.
+pub fn required_ticket(
+    branch: Option<&str>,
+    config: &CommitsConfig,
+    branch_config: &BranchConfig,
+) -> Option<String> {
+    if !rule_enabled(config, "ticket-trailer") {
+        return None;
+    }
+    let branch = branch::current_branch(branch).ok()?;
+    branch::branch_ticket(&branch, branch_config).ok().flatten()
+}
+
+/// Check breaking change and reference trailers, returning whether a valid
+/// `BREAKING CHANGE:` footer was found
+fn lint_footers(
+    body: &[(usize, &str)],
+    push: &mut impl FnMut(&'static str, usize, String),
+) -> bool {
+    static BREAKING_LIKE: OnceLock<Regex> = OnceLock::new();
+    static BREAKING: OnceLock<Regex> = OnceLock::new();
+    static TRAILER_LIKE: OnceLock<Regex> = OnceLock::new();
+    let breaking_like = BREAKING_LIKE.get_or_init(|| Regex::new(BREAKING_LIKE_PATTERN).unwrap());
+    let breaking = BREAKING.get_or_init(|| Regex::new(BREAKING_FOOTER_PATTERN).unwrap());
+    let trailer_like = TRAILER_LIKE.get_or_init(|| Regex::new(TRAILER_LIKE_PATTERN).unwrap());
+    let mut found = false;
+
+    for &(line, text) in body {
+        if breaking.is_match(text) {
+            found = true;
+        } else if breaking_like.is_match(text) {
+            push(
+                "breaking-change-format",
+                line,
+                format!("Use 'BREAKING CHANGE: <description>' instead of '{}'", text),
+            );
+        } else if trailer_like.is_match(text) && !trailer_regex().is_match(text) {
+            push(
+                "trailer-format",
+                line,
+                format!(
+                    "Invalid trailer '{}'; use e.g. 'Refs: #12' or 'Closes: #12, #13'",
+                    text
+                ),
+            );
+        }
+    }
+
+    found
+}
+
+fn trailer_regex() -> &'static Regex {
+    static TRAILER: OnceLock<Regex> = OnceLock::new();
+    TRAILER.get_or_init(|| Regex::new(TRAILER_PATTERN).unwrap())
+}
+
+/// "Unknown type 'feet'. Did you mean 'feat'?" or the list of valid values
+fn unknown(what: &str, value: &str, allowed: &[String]) -> String {
+    match suggest(value, allowed) {
+        Some(best) => format!("Unknown {} '{}'. Did you mean '{}'?", what, value, best),
+        None => format!(
+            "Unknown {} '{}'. Allowed: {}",
+            what,
+            value,
+            allowed.join(", ")
+        ),
+    }
+}
+
+/// Closest allowed value by edit distance
+pub fn suggest<'a>(value: &str, allowed: &'a [String]) -> Option<&'a str> {
+    let value = value.to_lowercase();
+    allowed
+        .iter()
+        .map(|a| (strsim::levenshtein(&value, a), a))
+        .filter(|(distance, a)| *distance <= MAX_SUGGESTION_DISTANCE.min(a.len() / 2 + 1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, a)| a.as_str())
+}
+
+fn report(result: &mut CheckResult, file: &str, issues: Vec<Issue>) {
+    for issue in issues {
+        println!("{} {}", "❌".red(), issue.message);
+        result.add_violation(Violation {
+            file: file.to_string(),
+            line: Some(issue.line),
+            message: issue.message,
+            rule: Some(issue.rule.to_string()),
+            ..Default::default()
+        });
+    }
+}
+
+pub fn check_commit_msg(
+    file: &str,
+    config: &CommitsConfig,
+    branch_config: &BranchConfig,
+) -> Result<CheckResult> {
+    let mut result = CheckResult::new("commit-msg");
+
+    let msg = read_message_file(file)?;
+    // Length has its own hook and command
+    let mut issues: Vec<Issue> = lint_message(&msg, config)
+        .into_iter()
+        .filter(|i| i.rule != "subject-max-length")
+        .collect();
+    if let Some(ticket) = required_ticket(None, config, branch_config) {
+        issues.extend(lint_ticket(&msg, &ticket, config));
+    }
+    // Without a usable repository (e.g. linting a message file elsewhere)
+    // there are no staged files to check the type and scope against
+    if let Ok(staged) = staged_files() {
+        issues.extend(lint_paths(&msg, &staged, config)?);
+    }
+
+    let header_rules = ["header-format", "type-enum", "scope-enum"];
+    if issues.iter().any(|i| header_rules.contains(&i.rule)) {
+        println!("{}", "❌ Invalid commit message format!".red());
+        println!("Use conventional commits: type(scope?): subject");
+        println!("Types: {}", config.types.join(", "));
+        if !config.scopes.is_empty() {
+            println!("Scopes: {}", config.scopes.join(", "));
+        }
+    } else if !issues.is_empty() {
+        println!(
+            "{}",
+            "❌ Commit message does not follow the commit policy".red()
+        );
+    }
+    report(&mut result, file, issues);
+
+    Ok(result)
+}
+
+pub fn check_commit_msg_length(file: &str, config: &CommitsConfig) -> Result<CheckResult> {
+    let mut result = CheckResult::new("commit-msg-length");
+
+    let msg = read_message_file(file)?;
+    let issues: Vec<Issue> = lint_message(&msg, config)
+        .into_iter()
+        .filter(|i| i.rule == "subject-max-length")
+        .collect();
+
+    if !issues.is_empty() {
+        println!("{}", "❌ Commit subject too long!".red());
+    }
+    report(&mut result, file, issues);
+
+    Ok(result)
+}
+
+/// A commit read from `git log` for range linting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedCommit {
+    pub sha: String,
+    pub author: String,
+    pub date: String,
+    pub message: String,
+}
+
+/// Parse `git log` output produced with the range log format
+fn parse_log(output: &str) -> Vec<LoggedCommit> {
+    output
+        .split(COMMIT_SEPARATOR)
+        .filter_map(|record| {
+            let mut fields = record.splitn(4, FIELD_SEPARATOR);
+            Some(LoggedCommit {
+                sha: fields.next()?.trim().to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                message: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `base..head`, with a bare revision meaning everything since it
+fn normalize_range(range: &str) -> String {
+    if range.contains("..") {
+        range.to_string()
+    } else {
+        format!("{}..HEAD", range)
+    }
+}
+
+/// Commits selected by `git log` revision arguments, oldest first
+pub fn log_commits(revs: &[&str], skip_merges: bool) -> Result<Vec<LoggedCommit>> {
+    log_commits_in(Path::new("."), revs, skip_merges)
+}
+
+/// [`log_commits`] in the repository at `repo`
+pub fn log_commits_in(repo: &Path, revs: &[&str], skip_merges: bool) -> Result<Vec<LoggedCommit>> {
+    let format = format!(
+        "--format={}%H{}%an <%ae>{}%aI{}%B",
+        COMMIT_SEPARATOR, FIELD_SEPARATOR, FIELD_SEPARATOR, FIELD_SEPARATOR
+    );
+    let mut args = vec!["log", "--reverse", "--no-color", format.as_str()];
+    if skip_merges {
+        args.push("--no-merges");
+    }
+    args.extend(revs);
+
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args(&args)
+        .output()
+        .context("Failed to run git log")?;
+    if !output.status.success() {
+        bail!(
+            "git log failed for {}: {}",
+            revs.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(parse_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Lint every commit in `base..head`, e.g. all commits of a pull request
+///
+/// Hooks can be skipped locally with `--no-verify` or `LEFTHOOK=0`, so CI
+/// re-runs the same rules as the commit-msg and commit-msg-length hooks.
+pub fn check_commit_range(
+    range: &str,
+    skip_merges: bool,
+    config: &CommitsConfig,
+    branch_config: &BranchConfig,
+) -> Result<CheckResult> {
+    let mut result = CheckResult::new("commit-range");
+    let range = normalize_range(range);
+
+    let commits = log_commits(&[&range], skip_merges)?;
+    let ticket = required_ticket(None, config, branch_config);
+    let mut failed = 0;
+    for commit in &commits {
+        let mut issues = lint_message(&commit.message, config);
+        if let Some(ticket) = &ticket {
+            issues.extend(lint_ticket(&commit.message, ticket, config));
+        }
+        issues.extend(lint_paths(
+            &commit.message,
+            &commit_files(&commit.sha)?,
+            config,
+        )?);
+        if issues.is_empty() {
+            continue;
+        }
+        failed += 1;
+        let subject = commit.message.lines().next().unwrap_or_default();
+        println!(
+            "{} {} {}",
+            "❌".red(),
+            short_sha(&commit.sha).yellow(),
+            subject
+        );
+        for issue in issues {
+            println!("   {}: {}", issue.rule, issue.message);
+            result.add_violation(Violation {
+                file: short_sha(&commit.sha).to_string(),
+                line: Some(issue.line),
+                message: issue.message,
+                rule: Some(issue.rule.to_string()),
+                commit: Some(commit.sha.clone()),
+                author: Some(commit.author.clone()),
+                date: Some(commit.date.clone()),
+                ..Default::default()
+            });
+        }
+    }
+
+    if failed == 0 {
+        println!(
+            "{}",
+            format!(
+                "✅ {} commit(s) in {} follow the commit policy",
+                commits.len(),
+                range
+            )
+            .green()
+        );
+    } else {
+        println!(
+            "{}",
+            format!(
+                "❌ {} of {} commit(s) in {} violate the commit policy",
+                failed,
+                commits.len(),
+                range
+            )
+            .red()
+        );
+        println!(
+            "Reword them with: git rebase -i {}",
+            range.split("..").next().unwrap_or_default()
+        );
+    }
+
+    Ok(result)
+}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CommitsConfig {
+        CommitsConfig {
+            scopes: ["monitors", "notifications", "auth", "cron"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn rules(message: &str) -> Vec<String> {
+        lint_message(message, &config())
+            .into_iter()
+            .map(|i| i.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let header = parse_header("feat(monitors, cron)!: add SSL checks").unwrap();
+        assert_eq!(header.kind, "feat");
+        assert_eq!(header.scopes, vec!["monitors", "cron"]);
+        assert!(header.breaking);
+        assert_eq!(header.subject, "add SSL checks");
+        assert!(parse_header("add SSL checks").is_none());
+    }
+
+    #[test]
+    fn test_body_and_footer_rules() {
+        let msg = "fix(cron): retry failed checks.\nThe body starts right away\n\nCloses #12\nbreaking change: the schedule moved\n# Please enter the commit message\n";
+        assert_eq!(
+            lint_message(&strip_comments(msg), &config())
+                .iter()
+                .map(|i| (i.line, i.rule))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, "body-leading-blank"),
+                (4, "trailer-format"),
+                (5, "breaking-change-format"),
+                (1, "subject-full-stop"),
+            ]
+        );
+
+        // `#` lines are comments in the message file only
+        let msg = "fix(cron): retry failed checks\n#12 was a race\n# ---- >8 ----\n";
+        assert!(lint_message(&strip_comments(msg), &config()).is_empty());
+        assert_eq!(lint_message(msg, &config())[0].rule, "body-leading-blank");
+        let scissors = format!("fix: x\n\nbody\n{}\ndiff --git a/x b/x\n", SCISSORS);
+        assert_eq!(strip_comments(&scissors), "fix: x\n\nbody\n");
+
+        let valid = format!(
+            "feat(auth)!: drop legacy sessions\n\n{}\nhttps://example.com/{}\n\nBREAKING CHANGE: sessions must be re-created\nRefs: #12, PROJ-7\n",
+            "Sessions created before the migration are invalidated.",
+            "a".repeat(120)
+        );
+        assert!(lint_message(&valid, &config()).is_empty());
+
+        let long = format!("docs: wrap\n\n{}", "word ".repeat(25));
+        assert_eq!(
+            lint_message(&long, &config())[0].rule,
+            "body-max-line-length"
+        );
+    }
+
+    #[test]
+    fn test_revert_fixup_and_rule_toggles() {
+        let revert =
+            "Revert \"feat(monitors): add SSL checks\"\n\nThis reverts commit 1a2b3c4d5e6f.\n";
+        assert!(lint_message(revert, &config()).is_empty());
+        assert_eq!(
+            lint_message("Revert \"feat: x\"", &config())[0].rule,
+            "revert-format"
+        );
+
+        assert!(lint_message("fixup! feat(auth): add login", &config()).is_empty());
+        let mut strict = config();
+        strict.rules.insert("no-fixup".to_string(), true);
+        strict
+            .rules
+            .insert("breaking-change-footer".to_string(), true);
+        strict.rules.insert("subject-full-stop".to_string(), false);
+        assert_eq!(
+            lint_message("squash! feat(auth): add login", &strict)[0].message,
+            "'squash!' commits must be squashed before merging"
+        );
+        assert_eq!(
+            lint_message("feat(auth)!: drop sessions.", &strict)
+                .iter()
+                .map(|i| i.rule)
+                .collect::<Vec<_>>(),
+            vec!["breaking-change-footer"]
+        );
+    }
+
+    #[test]
+    fn test_ticket_trailer() {
+        let mut config = config();
+        assert!(lint_ticket("feat(auth): add login", "PROJ-12", &config).is_none());
+
+        config.rules.insert("ticket-trailer".to_string(), true);
+        let issue = lint_ticket("feat(auth): add login\n\nRefs: PROJ-1", "PROJ-12", &config);
+        assert_eq!(issue.unwrap().line, 3);
+        assert!(lint_ticket(
+            "feat(auth): add login\n\nCloses: #3, PROJ-12",
+            "PROJ-12",
+            &config
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_type_and_scope_match_staged_paths() {
+        let mut config = config();
+        config
+            .scope_paths
+            .insert("db".to_string(), vec!["supabase/migrations/**".to_string()]);
+        config
+            .required_scopes
+            .insert("db".to_string(), "Migration".to_string());
+        let paths = |paths: &[&str]| paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let rules = |message: &str, staged: &[&str]| {
+            lint_paths(message, &paths(staged), &config)
+                .unwrap()
+                .into_iter()
+                .map(|i| i.rule)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(rules("docs: update README", &["README.md", "docs/setup.md"]).is_empty());
+        assert_eq!(
+            lint_paths("docs: update README", &paths(&["README.md", "lib/utils.ts"]), &config)
+                .unwrap()[0]
+                .message,
+            "'docs' commits should only touch **/*.md, **/*.mdx, docs/**, LICENSE*, but also change lib/utils.ts"
+        );
+        assert_eq!(
+            rules("test: cover retries", &["lib/qstash.ts"]),
+            vec!["type-paths"]
+        );
+        assert!(rules("test: cover retries", &["lib/qstash.test.ts"]).is_empty());
+
+        let migration = [
+            "supabase/migrations/002_groups.sql",
+            "lib/actions/groups.ts",
+        ];
+        assert_eq!(
+            rules("feat(monitors): add groups", &migration),
+            vec!["scope-paths"]
+        );
+        assert!(rules("feat(monitors, db): add groups", &migration).is_empty());
+        assert!(rules(
+            "feat(monitors): add groups\n\nMigration: 002_groups.sql",
+            &migration
+        )
+        .is_empty());
+        assert!(rules("docs: update README", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_log_and_range() {
+        let output = "\x1eabc123\x1fAda <ada@example.com>\x1f2024-05-01T10:00:00+00:00\x1ffeat(auth): add login\n\nRefs: #4\n\n\x1edef456\x1fAda <ada@example.com>\x1f2024-05-02T10:00:00+00:00\x1fupdate stuff\n\n";
+        let commits = parse_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].sha, "abc123");
+        assert_eq!(commits[1].date, "2024-05-02T10:00:00+00:00");
+        assert!(lint_message(&commits[0].message, &config()).is_empty());
+        assert_eq!(
+            lint_message(&commits[1].message, &config())[0].rule,
+            "header-format"
+        );
+
+        assert_eq!(normalize_range("origin/main"), "origin/main..HEAD");
+        assert_eq!(normalize_range("main...feature"), "main...feature");
+    }
+
+    #[test]
+    fn test_types_and_scopes_with_suggestions() {
+        assert!(rules("fix(auth): resolve login redirect").is_empty());
+        assert!(rules("Merge branch 'main' into feature/x").is_empty());
+        assert_eq!(
+            rules("feet(auth): resolve login redirect"),
+            vec!["Unknown type 'feet'. Did you mean 'feat'?"]
+        );
+        assert_eq!(
+            rules("fix(monitor): handle timeouts"),
+            vec!["Unknown scope 'monitor'. Did you mean 'monitors'?"]
+        );
+        assert_eq!(
+            rules("fix(billing): handle timeouts"),
+            vec!["Unknown scope 'billing'. Allowed: monitors, notifications, auth, cron"]
+        );
+        assert_eq!(
+            rules(&format!("feat: {}", "x".repeat(70))),
+            vec!["Commit subject is 76 characters (max 72)"]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -12,11 +13,29 @@ pub struct Config {
     #[serde(default)]
     pub files: FilesConfig,
     #[serde(default)]
+    pub env: EnvConfig,
+    #[serde(default)]
+    pub commits: CommitsConfig,
+    #[serde(default)]
+    pub branch: BranchConfig,
+    #[serde(default)]
+    pub changelog: ChangelogConfig,
+    #[serde(default)]
+    pub release: ReleaseConfig,
+    #[serde(default)]
+    pub signatures: SignaturesConfig,
+    #[serde(default)]
+    pub pr_size: PrSizeConfig,
+    #[serde(default)]
+    pub json: JsonConfig,
+    #[serde(default)]
+    pub pre_push: PrePushConfig,
+    #[serde(default)]
     #[allow(dead_code)] // Reserved for future use
     pub output: OutputConfig,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Default)]
 pub struct SecretsConfig {
     /// Additional secret patterns to check (regex)
     #[serde(default)]
@@ -25,13 +44,42 @@ pub struct SecretsConfig {
     #[serde(default)]
     #[allow(dead_code)] // Reserved for future use
     pub exclude_patterns: Vec<String>,
+    /// Limits for archive and encoded content decoding
+    #[serde(default)]
+    pub decode: DecodeConfig,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecodeConfig {
+    /// Maximum nesting of archives inside archives (default: 3)
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    /// Maximum number of archive entries scanned per file (default: 1000)
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+    /// Maximum decompressed bytes per file, across all entries (default: 50MB)
+    #[serde(default = "default_max_decoded_bytes")]
+    pub max_decoded_bytes: u64,
+}
+
+fn default_max_depth() -> usize {
+    3
 }
 
-impl Default for SecretsConfig {
+fn default_max_entries() -> usize {
+    1000
+}
+
+fn default_max_decoded_bytes() -> u64 {
+    50 * 1024 * 1024 // 50MB
+}
+
+impl Default for DecodeConfig {
     fn default() -> Self {
         Self {
-            extra_patterns: vec![],
-            exclude_patterns: vec![],
+            max_depth: default_max_depth(),
+            max_entries: default_max_entries(),
+            max_decoded_bytes: default_max_decoded_bytes(),
         }
     }
 }
@@ -43,17 +91,20 @@ pub struct DebugConfig {
     pub extra_patterns: Vec<String>,
     /// File extensions to check for debug statements
     #[serde(default = "default_debug_extensions")]
-    #[allow(dead_code)] // Reserved for future use
     pub extensions: Vec<String>,
+    /// Per-language overrides and additional languages
+    #[serde(default)]
+    pub languages: BTreeMap<String, LanguageConfig>,
+    /// Path-scoped console logging policies
+    #[serde(default)]
+    pub policies: Vec<PolicyConfig>,
 }
 
 fn default_debug_extensions() -> Vec<String> {
-    vec![
-        "ts".to_string(),
-        "tsx".to_string(),
-        "js".to_string(),
-        "jsx".to_string(),
-    ]
+    ["ts", "tsx", "js", "jsx", "mjs", "cjs", "rs", "sql"]
+        .iter()
+        .map(|e| e.to_string())
+        .collect()
 }
 
 impl Default for DebugConfig {
@@ -61,10 +112,39 @@ impl Default for DebugConfig {
         Self {
             extra_patterns: vec![],
             extensions: default_debug_extensions(),
+            languages: BTreeMap::new(),
+            policies: vec![],
         }
     }
 }
 
+#[derive(Debug, Deserialize, Default)]
+pub struct LanguageConfig {
+    /// File extensions of the language (replaces the built-in list)
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Globs of files the language rules apply to (replaces the built-in globs)
+    #[serde(default)]
+    pub globs: Vec<String>,
+    /// Additional debug statement patterns (regex)
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct PolicyConfig {
+    /// Name reported when the policy is broken
+    pub name: String,
+    /// Globs of files the policy applies to
+    pub paths: Vec<String>,
+    /// Console calls allowed under these paths; any other call is a violation
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Console calls forbidden under these paths (e.g. "console.*")
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct FilesConfig {
     /// Maximum file size in bytes (default: 500KB)
@@ -88,19 +168,482 @@ impl Default for FilesConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
-pub enum OutputFormat {
-    Text,
-    Json,
+#[derive(Debug, Deserialize)]
+pub struct EnvConfig {
+    /// Local env file checked by env-check (default: .env.local)
+    #[serde(default = "default_env_file")]
+    pub file: String,
+    /// Documented example env file (default: .env.local.example)
+    #[serde(default = "default_env_example")]
+    pub example: String,
+    /// Minimum value lengths, on top of "minimum N characters" hints in the example
+    #[serde(default = "default_min_lengths")]
+    pub min_lengths: BTreeMap<String, usize>,
+    /// Prefixes of platform-injected keys never reported as unknown
+    #[serde(default = "default_allowed_prefixes")]
+    pub allowed_prefixes: Vec<String>,
+    /// Files and directories searched for `process.env` references
+    #[serde(default = "default_env_sources")]
+    pub sources: Vec<String>,
+    /// Documented variables read outside the app code (e.g. by Next.js itself)
+    #[serde(default = "default_env_external")]
+    pub external: Vec<String>,
+}
+
+fn default_env_file() -> String {
+    ".env.local".to_string()
+}
+
+fn default_env_example() -> String {
+    ".env.local.example".to_string()
+}
+
+fn default_min_lengths() -> BTreeMap<String, usize> {
+    BTreeMap::from([("CRON_SECRET".to_string(), 32)])
+}
+
+fn default_allowed_prefixes() -> Vec<String> {
+    vec!["VERCEL_".to_string(), "TURBO_".to_string()]
+}
+
+fn default_env_sources() -> Vec<String> {
+    vec![
+        "app".to_string(),
+        "lib".to_string(),
+        "components".to_string(),
+        "proxy.ts".to_string(),
+    ]
+}
+
+fn default_env_external() -> Vec<String> {
+    vec!["NEXT_TELEMETRY_DISABLED".to_string()]
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            file: default_env_file(),
+            example: default_env_example(),
+            min_lengths: default_min_lengths(),
+            allowed_prefixes: default_allowed_prefixes(),
+            sources: default_env_sources(),
+            external: default_env_external(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitsConfig {
+    /// Allowed conventional commit types
+    #[serde(default = "default_commit_types")]
+    pub types: Vec<String>,
+    /// Allowed scopes (empty allows any scope)
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Maximum subject line length (default: 72)
+    #[serde(default = "default_subject_max_length")]
+    pub subject_max_length: usize,
+    /// Maximum body line length (default: 100)
+    #[serde(default = "default_body_max_line_length")]
+    pub body_max_line_length: usize,
+    /// Enable or disable individual rules by name (e.g. "subject-full-stop")
+    #[serde(default)]
+    pub rules: BTreeMap<String, bool>,
+    /// Scope inferred by prepare-commit-msg for staged files matching the globs
+    #[serde(default)]
+    pub scope_paths: BTreeMap<String, Vec<String>>,
+    /// Commit type pre-filled for each branch type (e.g. feature = "feat")
+    #[serde(default = "default_branch_types_map")]
+    pub branch_types: BTreeMap<String, String>,
+    /// Which staged files each commit type may or must touch
+    #[serde(default = "default_type_paths")]
+    pub type_paths: BTreeMap<String, TypePaths>,
+    /// Scopes required when staged files match their `scope_paths`, mapped to
+    /// a trailer that may be used instead (empty for none)
+    #[serde(default)]
+    pub required_scopes: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct TypePaths {
+    /// Every staged file must match one of these globs
+    #[serde(default)]
+    pub only: Vec<String>,
+    /// At least one staged file must match one of these globs
+    #[serde(default)]
+    pub any: Vec<String>,
+}
+
+fn default_commit_types() -> Vec<String> {
+    [
+        "feat", "fix", "docs", "style", "refactor", "perf", "test", "chore", "build", "ci",
+        "revert",
+    ]
+    .iter()
+    .map(|t| t.to_string())
+    .collect()
+}
+
+fn default_branch_types_map() -> BTreeMap<String, String> {
+    [
+        ("feature", "feat"),
+        ("fix", "fix"),
+        ("hotfix", "fix"),
+        ("release", "chore"),
+        ("chore", "chore"),
+        ("docs", "docs"),
+        ("refactor", "refactor"),
+        ("test", "test"),
+    ]
+    .iter()
+    .map(|(branch, commit)| (branch.to_string(), commit.to_string()))
+    .collect()
+}
+
+fn default_type_paths() -> BTreeMap<String, TypePaths> {
+    let globs = |globs: &[&str]| globs.iter().map(|g| g.to_string()).collect();
+    BTreeMap::from([
+        (
+            "docs".to_string(),
+            TypePaths {
+                only: globs(&["**/*.md", "**/*.mdx", "docs/**", "LICENSE*"]),
+                any: vec![],
+            },
+        ),
+        (
+            "test".to_string(),
+            TypePaths {
+                only: vec![],
+                any: globs(&[
+                    "**/*.test.*",
+                    "**/*.spec.*",
+                    "**/__tests__/**",
+                    "tests/**",
+                    "e2e/**",
+                ]),
+            },
+        ),
+    ])
+}
+
+fn default_subject_max_length() -> usize {
+    72
+}
+
+fn default_body_max_line_length() -> usize {
+    100
+}
+
+impl Default for CommitsConfig {
+    fn default() -> Self {
+        Self {
+            types: default_commit_types(),
+            scopes: vec![],
+            subject_max_length: default_subject_max_length(),
+            body_max_line_length: default_body_max_line_length(),
+            rules: BTreeMap::new(),
+            scope_paths: BTreeMap::new(),
+            branch_types: default_branch_types_map(),
+            type_paths: default_type_paths(),
+            required_scopes: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BranchConfig {
+    /// Allowed branch types, the part before the `/`
+    #[serde(default = "default_branch_types")]
+    pub types: Vec<String>,
+    /// Branches that are never checked
+    #[serde(default = "default_branch_exempt")]
+    pub exempt: Vec<String>,
+    /// Regex for the description after `type/` and the optional ticket id
+    #[serde(default = "default_branch_description")]
+    pub description_pattern: String,
+    /// Regex for ticket ids such as `PROJ-123`
+    #[serde(default = "default_ticket_pattern")]
+    pub ticket_pattern: String,
+    /// Fail when the branch name has no ticket id
+    #[serde(default)]
+    pub require_ticket: bool,
+    /// Branch that `audit branches` checks merges against
+    #[serde(default = "default_branch_base")]
+    pub base: String,
+    /// Days without commits before `audit branches` reports a branch as inactive
+    #[serde(default = "default_stale_days")]
+    pub stale_days: u64,
+}
+
+fn default_branch_types() -> Vec<String> {
+    [
+        "feature", "fix", "hotfix", "release", "chore", "docs", "refactor", "test",
+    ]
+    .iter()
+    .map(|t| t.to_string())
+    .collect()
 }
 
-impl Default for OutputFormat {
+fn default_branch_exempt() -> Vec<String> {
+    ["main", "master", "develop"]
+        .iter()
+        .map(|b| b.to_string())
+        .collect()
+}
+
+fn default_branch_description() -> String {
+    "[a-zA-Z0-9-]*[a-zA-Z0-9]".to_string()
+}
+
+fn default_branch_base() -> String {
+    "main".to_string()
+}
+
+fn default_stale_days() -> u64 {
+    90
+}
+
+fn default_ticket_pattern() -> String {
+    "[A-Z][A-Z0-9]+-[0-9]+".to_string()
+}
+
+impl Default for BranchConfig {
+    fn default() -> Self {
+        Self {
+            types: default_branch_types(),
+            exempt: default_branch_exempt(),
+            description_pattern: default_branch_description(),
+            ticket_pattern: default_ticket_pattern(),
+            require_ticket: false,
+            base: default_branch_base(),
+            stale_days: default_stale_days(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangelogConfig {
+    /// Changelog updated by `changelog --write`
+    #[serde(default = "default_changelog_file")]
+    pub file: String,
+    /// Commit types included in the changelog, in section order
+    #[serde(default = "default_changelog_sections")]
+    pub sections: Vec<ChangelogSection>,
+    /// Link template for commits, with `{sha}` replaced by the full hash
+    #[serde(default)]
+    pub commit_url: Option<String>,
+    /// Issue trackers whose references are turned into links
+    #[serde(default)]
+    pub trackers: Vec<TrackerConfig>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ChangelogSection {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub title: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TrackerConfig {
+    /// Regex for a reference; the first capture group (or the whole match) is the id
+    pub pattern: String,
+    /// Link template with `{id}` replaced by the reference id
+    pub url: String,
+}
+
+fn default_changelog_file() -> String {
+    "CHANGELOG.md".to_string()
+}
+
+fn default_changelog_sections() -> Vec<ChangelogSection> {
+    [
+        ("feat", "Features"),
+        ("fix", "Bug Fixes"),
+        ("perf", "Performance Improvements"),
+        ("revert", "Reverts"),
+    ]
+    .iter()
+    .map(|(kind, title)| ChangelogSection {
+        kind: kind.to_string(),
+        title: title.to_string(),
+    })
+    .collect()
+}
+
+impl Default for ChangelogConfig {
+    fn default() -> Self {
+        Self {
+            file: default_changelog_file(),
+            sections: default_changelog_sections(),
+            commit_url: None,
+            trackers: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseConfig {
+    /// Branches releases may be cut from
+    #[serde(default = "default_release_branches")]
+    pub branches: Vec<String>,
+    /// package.json, package-lock.json or Cargo.toml files whose version is bumped
+    #[serde(default = "default_release_manifests")]
+    pub manifests: Vec<String>,
+    /// Prefix of release tags, e.g. `v` for `v1.2.0`
+    #[serde(default = "default_tag_prefix")]
+    pub tag_prefix: String,
+}
+
+fn default_release_branches() -> Vec<String> {
+    vec!["main".to_string(), "master".to_string()]
+}
+
+fn default_release_manifests() -> Vec<String> {
+    [
+        "package.json",
+        "package-lock.json",
+        "tools/audit/Cargo.toml",
+    ]
+    .iter()
+    .map(|m| m.to_string())
+    .collect()
+}
+
+fn default_tag_prefix() -> String {
+    "v".to_string()
+}
+
+impl Default for ReleaseConfig {
     fn default() -> Self {
-        Self::Text
+        Self {
+            branches: default_release_branches(),
+            manifests: default_release_manifests(),
+            tag_prefix: default_tag_prefix(),
+        }
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SignaturesConfig {
+    /// SSH allowed-signers file, plus `<principal> gpg <FINGERPRINT>` lines
+    #[serde(default = "default_allowed_signers")]
+    pub allowed_signers: String,
+}
+
+fn default_allowed_signers() -> String {
+    ".github/allowed_signers".to_string()
+}
+
+impl Default for SignaturesConfig {
+    fn default() -> Self {
+        Self {
+            allowed_signers: default_allowed_signers(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PrSizeConfig {
+    /// Maximum changed lines (added + removed) across the whole diff
+    #[serde(default = "default_pr_max_lines")]
+    pub max_lines: usize,
+    /// Files not counted: lockfiles, generated code, static assets
+    #[serde(default = "default_pr_exclude")]
+    pub exclude: Vec<String>,
+    /// Maximum changed lines for files matching each glob
+    #[serde(default)]
+    pub budgets: BTreeMap<String, usize>,
+}
+
+fn default_pr_max_lines() -> usize {
+    800
+}
+
+fn default_pr_exclude() -> Vec<String> {
+    [
+        "**/package-lock.json",
+        "**/yarn.lock",
+        "**/pnpm-lock.yaml",
+        "**/Cargo.lock",
+        "public/**",
+        "**/*.min.*",
+        "**/*.map",
+        "**/*.snap",
+    ]
+    .iter()
+    .map(|g| g.to_string())
+    .collect()
+}
+
+impl Default for PrSizeConfig {
+    fn default() -> Self {
+        Self {
+            max_lines: default_pr_max_lines(),
+            exclude: default_pr_exclude(),
+            budgets: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JsonConfig {
+    /// Glob to schema: a bundled schema name or a schema file path.
+    /// Files matching none fall back to a bundled schema for their `$schema`
+    #[serde(default = "default_json_schemas")]
+    pub schemas: BTreeMap<String, String>,
+}
+
+fn default_json_schemas() -> BTreeMap<String, String> {
+    [
+        ("vercel.json", "vercel"),
+        ("components.json", "components"),
+        ("**/site.webmanifest", "webmanifest"),
+        ("**/tsconfig.json", "tsconfig"),
+        ("**/package.json", "package"),
+    ]
+    .iter()
+    .map(|(glob, schema)| (glob.to_string(), schema.to_string()))
+    .collect()
+}
+
+impl Default for JsonConfig {
+    fn default() -> Self {
+        Self {
+            schemas: default_json_schemas(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PrePushConfig {
+    /// Globs of pushed files scanned for secrets; the pre-commit
+    /// secrets-check glob in lefthook.yml, so fixtures in other files pass
+    #[serde(default = "default_pre_push_secrets_paths")]
+    pub secrets_paths: Vec<String>,
+}
+
+fn default_pre_push_secrets_paths() -> Vec<String> {
+    vec!["*.{ts,tsx,js,jsx,json,toml,yml,yaml,sql,env*,zip,gz,tgz,tar,ico,png}".to_string()]
+}
+
+impl Default for PrePushConfig {
+    fn default() -> Self {
+        Self {
+            secrets_paths: default_pre_push_secrets_paths(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Sarif,
+}
+
 #[derive(Debug, Deserialize, Default)]
 pub struct OutputConfig {
     #[serde(default)]
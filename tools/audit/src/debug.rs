@@ -0,0 +1,613 @@
+use crate::config::{Config, DebugConfig, PolicyConfig};
+use crate::engine::{Rule, RuleSet};
+use crate::lexer::{JsLexer, JS_EXTENSIONS};
+use crate::{has_ignore_marker, redact, should_skip, CheckResult, Violation};
+use anyhow::Result;
+use colored::*;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rayon::prelude::*;
+use regex::Regex;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// A built-in debug statement pattern
+struct Pattern {
+    id: &'static str,
+    regex: &'static str,
+    keywords: &'static [&'static str],
+    /// Globs of files where this pattern is legitimate
+    except: &'static [&'static str],
+}
+
+/// How comments and strings are removed before matching
+#[derive(Debug, Clone, Copy)]
+enum Masking {
+    /// Full JS/TS lexer (strings, comments, template literals, JSX text)
+    Js,
+    /// Drop line comments that start outside `quote`-delimited strings
+    LineComment {
+        marker: &'static str,
+        quote: char,
+    },
+    None,
+}
+
+/// A built-in language with its files and debug patterns
+struct Language {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    globs: &'static [&'static str],
+    masking: Masking,
+    patterns: &'static [Pattern],
+}
+
+/// Debug statements, matched against code with strings and comments masked
+const LANGUAGES: &[Language] = &[
+    Language {
+        name: "javascript",
+        extensions: JS_EXTENSIONS,
+        globs: &["**/*"],
+        masking: Masking::Js,
+        patterns: &[
+            Pattern {
+                id: "console-log",
+                regex: r"(?:^|[^\w$.])console\.log\s*\(",
+                keywords: &["console.log"],
+                except: &[],
+            },
+            Pattern {
+                id: "console-debug",
+                regex: r"(?:^|[^\w$.])console\.debug\s*\(",
+                keywords: &["console.debug"],
+                except: &[],
+            },
+            Pattern {
+                id: "debugger",
+                regex: r"(?:^|[^\w$.])debugger\b",
+                keywords: &["debugger"],
+                except: &[],
+            },
+            Pattern {
+                id: "alert",
+                regex: r"(?:^|[^\w$.])(?:window\.)?alert\s*\(",
+                keywords: &["alert"],
+                except: &[],
+            },
+        ],
+    },
+    Language {
+        name: "rust",
+        extensions: &["rs"],
+        globs: &["**/*.rs"],
+        masking: Masking::LineComment {
+            marker: "//",
+            quote: '"',
+        },
+        patterns: &[
+            Pattern {
+                id: "dbg",
+                regex: r"(?:^|[^\w])dbg!\s*\(",
+                keywords: &["dbg!"],
+                except: &[],
+            },
+            Pattern {
+                id: "println",
+                regex: r"(?:^|[^\w])println!\s*\(",
+                keywords: &["println!"],
+                except: &["**/main.rs"],
+            },
+        ],
+    },
+    Language {
+        name: "sql",
+        extensions: &["sql"],
+        globs: &["**/migrations/**/*.sql"],
+        masking: Masking::LineComment {
+            marker: "--",
+            quote: '\'',
+        },
+        patterns: &[Pattern {
+            id: "raise-notice",
+            regex: r"(?i)\braise\s+notice\b",
+            keywords: &["raise"],
+            except: &[],
+        }],
+    },
+];
+
+/// Debug rules compiled for one language
+pub struct LanguageRules {
+    pub name: String,
+    extensions: Vec<String>,
+    globs: GlobSet,
+    masking: Masking,
+    rules: RuleSet,
+    ids: Vec<String>,
+    except: Vec<GlobSet>,
+}
+
+impl LanguageRules {
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Strips comments and strings from the lines of `path`
+    pub fn masker(&self, path: &str) -> Masker {
+        match self.masking {
+            Masking::Js => {
+                Masker::Js(JsLexer::for_path(path).unwrap_or_else(|| JsLexer::new(false)))
+            }
+            Masking::LineComment { marker, quote } => Masker::LineComment { marker, quote },
+            Masking::None => Masker::None,
+        }
+    }
+
+    /// Id of the first pattern matching masked `code` that applies to `path`
+    pub fn first_match<'a>(&'a self, path: &'a str, code: &str) -> Option<&'a str> {
+        self.matches(path, code).next()
+    }
+
+    /// Ids of every pattern matching masked `code` that applies to `path`
+    pub fn matches<'a>(&'a self, path: &'a str, code: &str) -> impl Iterator<Item = &'a str> {
+        self.rules
+            .matches(code)
+            .into_iter()
+            .filter(move |&i| !self.except[i].is_match(path))
+            .map(|i| self.ids[i].as_str())
+    }
+}
+
+/// Stateful per-file masking
+pub enum Masker {
+    Js(JsLexer),
+    LineComment { marker: &'static str, quote: char },
+    None,
+}
+
+impl Masker {
+    pub fn mask_line(&mut self, line: &str) -> String {
+        match self {
+            Masker::Js(lexer) => lexer.mask_line(line),
+            Masker::LineComment { marker, quote } => strip_line_comment(line, marker, *quote),
+            Masker::None => line.to_string(),
+        }
+    }
+}
+
+/// `console.<method>(` calls, matched on masked code
+const CONSOLE_CALL_PATTERN: &str = r"(?:^|[^\w$.])(console\.\w+)\s*\(";
+
+/// A path-scoped logging policy
+struct Policy {
+    name: String,
+    paths: GlobSet,
+    /// When set, the only console calls allowed under `paths`
+    allow: Option<(GlobSet, String)>,
+    deny: GlobSet,
+}
+
+impl Policy {
+    fn new(config: &PolicyConfig) -> Self {
+        let allow = (!config.allow.is_empty())
+            .then(|| (method_set(&config.allow), config.allow.join(", ")));
+        Self {
+            name: config.name.clone(),
+            paths: glob_set(config.paths.iter().map(String::as_str)),
+            allow,
+            deny: method_set(&config.deny),
+        }
+    }
+
+    fn allows(&self, call: &str) -> bool {
+        self.allow
+            .as_ref()
+            .is_some_and(|(set, _)| set.is_match(call))
+    }
+
+    /// Why `call` breaks this policy, if it does
+    fn violation(&self, call: &str) -> Option<String> {
+        if self.deny.is_match(call) {
+            return Some(format!("{} is forbidden by policy '{}'", call, self.name));
+        }
+        match &self.allow {
+            Some((set, list)) if !set.is_match(call) => Some(format!(
+                "{} is not allowed by policy '{}' (allows {})",
+                call, self.name, list
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Method patterns like `console.*` are globs without path separators
+fn method_set(methods: &[String]) -> GlobSet {
+    glob_set(methods.iter().map(String::as_str))
+}
+
+/// Path-scoped allow/deny policies for console calls
+pub struct Policies {
+    policies: Vec<Policy>,
+    console_call: Regex,
+}
+
+impl Policies {
+    pub fn new(config: &DebugConfig) -> Self {
+        Self {
+            policies: config.policies.iter().map(Policy::new).collect(),
+            console_call: Regex::new(CONSOLE_CALL_PATTERN).unwrap(),
+        }
+    }
+
+    fn calls<'a>(&self, code: &'a str) -> Vec<&'a str> {
+        self.console_call
+            .captures_iter(code)
+            .filter_map(|caps| caps.get(1))
+            .map(|m| m.as_str())
+            .collect()
+    }
+
+    fn applicable<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a Policy> {
+        let path = path.trim_start_matches("./");
+        self.policies.iter().filter(move |p| p.paths.is_match(path))
+    }
+
+    /// The first broken policy on a masked line, as (rule, message)
+    pub fn check(&self, path: &str, code: &str) -> Option<(String, String)> {
+        if self.policies.is_empty() {
+            return None;
+        }
+        self.calls(code).into_iter().find_map(|call| {
+            self.applicable(path)
+                .find_map(|p| p.violation(call).map(|m| (format!("policy/{}", p.name), m)))
+        })
+    }
+
+    /// Whether a policy explicitly allows the call a `console-*` rule reports
+    pub fn allows_rule(&self, path: &str, rule: &str) -> bool {
+        let Some(method) = rule.strip_prefix("console-") else {
+            return false;
+        };
+        let call = format!("console.{}", method);
+        self.applicable(path).any(|p| p.allows(&call))
+    }
+}
+
+/// Per-language debug rules selected by file extension and glob
+pub struct DebugRules {
+    languages: Vec<LanguageRules>,
+    extensions: Vec<String>,
+}
+
+impl DebugRules {
+    pub fn new(config: &DebugConfig) -> Self {
+        let mut languages: Vec<LanguageRules> = LANGUAGES
+            .iter()
+            .map(|lang| {
+                let overrides = config.languages.get(lang.name);
+                let pick = |custom: Option<&Vec<String>>, builtin: &[&str]| match custom {
+                    Some(custom) if !custom.is_empty() => custom.clone(),
+                    _ => builtin.iter().map(|s| s.to_string()).collect(),
+                };
+                let builtin = lang.patterns.iter().map(|p| {
+                    (
+                        p.id.to_string(),
+                        p.regex.to_string(),
+                        p.keywords.to_vec(),
+                        glob_set(p.except.iter().copied()),
+                    )
+                });
+                let custom = overrides.map(|o| o.patterns.as_slice()).unwrap_or_default();
+                compile_language(
+                    lang.name,
+                    pick(overrides.map(|o| &o.extensions), lang.extensions),
+                    &pick(overrides.map(|o| &o.globs), lang.globs),
+                    lang.masking,
+                    builtin.collect(),
+                    custom,
+                    &config.extra_patterns,
+                )
+            })
+            .collect();
+
+        // Languages only defined in the config have no masking
+        for (name, lang) in &config.languages {
+            if LANGUAGES.iter().any(|l| l.name == name) {
+                continue;
+            }
+            let globs = if lang.globs.is_empty() {
+                vec!["**/*".to_string()]
+            } else {
+                lang.globs.clone()
+            };
+            languages.push(compile_language(
+                name,
+                lang.extensions.clone(),
+                &globs,
+                Masking::None,
+                vec![],
+                &lang.patterns,
+                &config.extra_patterns,
+            ));
+        }
+
+        Self {
+            languages,
+            extensions: config.extensions.clone(),
+        }
+    }
+
+    /// Total number of patterns across languages
+    pub fn len(&self) -> usize {
+        self.languages.iter().map(|l| l.len()).sum()
+    }
+
+    /// Rules for `path`, if its extension is enabled and a language claims it
+    pub fn language(&self, path: &str) -> Option<&LanguageRules> {
+        let ext = Path::new(path).extension()?.to_str()?;
+        if !self.extensions.iter().any(|e| e == ext) {
+            return None;
+        }
+        let path = path.trim_start_matches("./");
+        self.languages
+            .iter()
+            .find(|l| l.extensions.iter().any(|e| e == ext) && l.globs.is_match(path))
+    }
+}
+
+fn compile_language(
+    name: &str,
+    extensions: Vec<String>,
+    globs: &[String],
+    masking: Masking,
+    builtin: Vec<(String, String, Vec<&str>, GlobSet)>,
+    custom: &[String],
+    extra: &[String],
+) -> LanguageRules {
+    let custom = custom.iter().chain(extra).enumerate().map(|(i, pattern)| {
+        (
+            format!("custom-{}", i + 1),
+            pattern.clone(),
+            vec![],
+            GlobSet::empty(),
+        )
+    });
+    let patterns: Vec<_> = builtin.into_iter().chain(custom).collect();
+
+    let rules = RuleSet::new(patterns.iter().map(|(_, regex, keywords, _)| Rule {
+        pattern: regex,
+        keywords: keywords.clone(),
+    }))
+    .expect("Invalid regex pattern");
+
+    LanguageRules {
+        name: name.to_string(),
+        extensions,
+        globs: glob_set(globs.iter().map(String::as_str)),
+        masking,
+        rules,
+        ids: patterns.iter().map(|(id, ..)| id.clone()).collect(),
+        except: patterns.into_iter().map(|(.., except)| except).collect(),
+    }
+}
+
+fn glob_set<'a>(globs: impl IntoIterator<Item = &'a str>) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        match Glob::new(glob) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => eprintln!("Warning: Invalid glob '{}': {}", glob, e),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+/// Cut a line at its comment marker, ignoring markers inside strings
+fn strip_line_comment(line: &str, marker: &str, quote: char) -> String {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        if c == quote {
+            in_string = !in_string;
+        } else if !in_string && line[i..].starts_with(marker) {
+            return line[..i].to_string();
+        }
+    }
+    line.to_string()
+}
+
+pub fn check_no_debug(files: &[String], config: &Config) -> Result<CheckResult> {
+    let mut result = CheckResult::new("no-debug");
+    let rules = DebugRules::new(&config.debug);
+    let policies = Policies::new(&config.debug);
+
+    let violations: Vec<Violation> = files
+        .par_iter()
+        .filter(|file| !should_skip(file, config))
+        .filter(|file| Path::new(file).exists())
+        .flat_map(|file| {
+            let Some(language) = rules.language(file) else {
+                return vec![];
+            };
+            match fs::File::open(file) {
+                Ok(f) => debug_violations(file, BufReader::new(f), language, &policies),
+                Err(_) => vec![],
+            }
+        })
+        .collect();
+
+    for v in violations {
+        result.add_violation(v);
+    }
+
+    Ok(result)
+}
+
+/// Debug statements and logging policy violations in one file's content
+pub fn debug_violations<R: BufRead>(
+    file: &str,
+    reader: R,
+    language: &LanguageRules,
+    policies: &Policies,
+) -> Vec<Violation> {
+    let mut file_violations = vec![];
+    let mut masker = language.masker(file);
+    // Binary files stop at the first invalid UTF-8 line
+    for (i, line) in reader.lines().map_while(Result::ok).enumerate() {
+        let code = masker.mask_line(&line);
+        if has_ignore_marker(&line) {
+            continue;
+        }
+        let builtin = language
+            .matches(file, &code)
+            .find(|id| !policies.allows_rule(file, id))
+            .map(|id| (format!("{}/{}", language.name, id), None));
+        let broken = policies
+            .check(file, &code)
+            .map(|(rule, message)| (rule, Some(message)));
+
+        if let Some((rule, message)) = broken.or(builtin) {
+            let snippet = redact::text(line.trim());
+            let header = match message {
+                Some(_) => "❌ Logging policy violation in",
+                None => "❌ Debug statement found in",
+            };
+            println!("{} {}:{}: {}", header.red(), file, i + 1, snippet);
+            if let Some(message) = &message {
+                println!("   {}", message);
+            }
+            file_violations.push(Violation {
+                file: file.to_string(),
+                line: Some(i + 1),
+                message: match message {
+                    Some(message) => format!("{}: {}", message, snippet),
+                    None => format!("Debug statement: {}", snippet),
+                },
+                rule: Some(rule),
+                ..Default::default()
+            });
+        }
+    }
+    file_violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(rules: &DebugRules, path: &str, line: &str) -> Option<String> {
+        let language = rules.language(path)?;
+        let code = language.masker(path).mask_line(line);
+        language.first_match(path, &code).map(str::to_string)
+    }
+
+    #[test]
+    fn test_js_rules_only_match_code() {
+        let rules = DebugRules::new(&DebugConfig::default());
+        let mut lexer = JsLexer::new(true);
+        let js = rules.language("components/a.tsx").unwrap();
+        let mut matches = |line: &str| js.first_match("components/a.tsx", &lexer.mask_line(line));
+
+        assert_eq!(matches("  console.log(user);"), Some("console-log"));
+        assert_eq!(matches("if (x) debugger;"), Some("debugger"));
+        assert_eq!(matches("window.alert('Saved')"), Some("alert"));
+        assert_eq!(matches("showAlert('Saved');"), None);
+        assert_eq!(
+            matches("const hint = 'remove the debugger statement';"),
+            None
+        );
+        assert_eq!(matches("// console.log(user)"), None);
+        assert_eq!(matches("<p>Use alert( sparingly</p>"), None);
+    }
+
+    #[test]
+    fn test_path_policies() {
+        let policy = |name: &str, paths: &[&str], allow: &[&str], deny: &[&str]| {
+            let strings = |v: &[&str]| v.iter().map(|s| s.to_string()).collect();
+            PolicyConfig {
+                name: name.to_string(),
+                paths: strings(paths),
+                allow: strings(allow),
+                deny: strings(deny),
+            }
+        };
+        let policies = Policies::new(&DebugConfig {
+            policies: vec![
+                policy(
+                    "server-logs",
+                    &["app/api/**", "lib/**"],
+                    &["console.error", "console.warn"],
+                    &[],
+                ),
+                policy("no-client-logging", &["components/**"], &[], &["console.*"]),
+            ],
+            ..Default::default()
+        });
+
+        assert_eq!(
+            policies.check("app/api/x/route.ts", "  console.error(e);"),
+            None
+        );
+        assert!(policies.allows_rule("lib/a.ts", "console-warn"));
+        assert!(!policies.allows_rule("lib/a.ts", "console-log"));
+        assert_eq!(
+            policies.check("lib/a.ts", "console.info(e)"),
+            Some((
+                "policy/server-logs".to_string(),
+                "console.info is not allowed by policy 'server-logs' (allows console.error, console.warn)".to_string()
+            ))
+        );
+        assert_eq!(
+            policies
+                .check("components/ui/a.tsx", "console.error(e)")
+                .unwrap()
+                .1,
+            "console.error is forbidden by policy 'no-client-logging'"
+        );
+        assert_eq!(policies.check("app/page.tsx", "console.error(e)"), None);
+
+        // Allowed calls do not hide other debug statements on the line
+        let rules = DebugRules::new(&DebugConfig::default());
+        let js = rules.language("lib/a.ts").unwrap();
+        let violations = debug_violations(
+            "lib/a.ts",
+            "debugger; console.error(e)\n".as_bytes(),
+            js,
+            &policies,
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule.as_deref(), Some("javascript/debugger"));
+    }
+
+    #[test]
+    fn test_languages_by_extension_and_glob() {
+        let rules = DebugRules::new(&DebugConfig::default());
+
+        assert_eq!(
+            matches(&rules, "src/lib.rs", "    dbg!(value);").as_deref(),
+            Some("dbg")
+        );
+        assert_eq!(
+            matches(&rules, "src/lib.rs", "    println!(\"x\");").as_deref(),
+            Some("println")
+        );
+        assert_eq!(matches(&rules, "src/main.rs", "    println!(\"x\");"), None);
+        assert_eq!(matches(&rules, "src/lib.rs", "    // dbg!(value);"), None);
+
+        let raise = "  RAISE NOTICE 'inserted %', id; -- debug";
+        assert_eq!(
+            matches(&rules, "supabase/migrations/001_init.sql", raise).as_deref(),
+            Some("raise-notice")
+        );
+        assert_eq!(matches(&rules, "supabase/seed.sql", raise), None);
+
+        // Disabled extensions are never checked
+        let js_only = DebugRules::new(&DebugConfig {
+            extensions: vec!["ts".to_string()],
+            ..Default::default()
+        });
+        assert!(js_only.language("src/lib.rs").is_none());
+        assert!(js_only.language("lib/a.ts").is_some());
+    }
+}
@@ -0,0 +1,157 @@
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use regex::{Regex, RegexSet};
+
+/// A pattern plus literal keywords, one of which appears in every match
+#[derive(Debug, Clone)]
+pub struct Rule<'a> {
+    pub pattern: &'a str,
+    pub keywords: Vec<&'a str>,
+}
+
+/// Rules compiled for single-pass matching
+///
+/// Lines first go through an Aho-Corasick keyword prefilter; only lines
+/// containing a keyword are run through the `RegexSet` of keyworded rules,
+/// which evaluates every pattern in one scan. Rules without keywords sit in a
+/// second set that runs on every line. Individual regexes are only used
+/// afterwards to locate the match of the winning rule.
+pub struct RuleSet {
+    regexes: Vec<Regex>,
+    filtered: RuleGroup,
+    unfiltered: RuleGroup,
+    prefilter: Option<AhoCorasick>,
+}
+
+/// Rules sharing one `RegexSet`, with their declaration-order indices
+struct RuleGroup {
+    set: RegexSet,
+    indices: Vec<usize>,
+}
+
+impl RuleGroup {
+    fn new(rules: &[Rule], indices: Vec<usize>) -> Result<Self, regex::Error> {
+        let set = RegexSet::new(indices.iter().map(|&i| rules[i].pattern))?;
+        Ok(Self { set, indices })
+    }
+
+    fn matches<'a>(&'a self, text: &str) -> impl Iterator<Item = usize> + 'a {
+        self.set.matches(text).into_iter().map(|i| self.indices[i])
+    }
+}
+
+impl RuleSet {
+    pub fn new<'a>(rules: impl IntoIterator<Item = Rule<'a>>) -> Result<Self, regex::Error> {
+        let rules: Vec<Rule<'a>> = rules.into_iter().collect();
+
+        let regexes = rules
+            .iter()
+            .map(|r| Regex::new(r.pattern))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (keyworded, keywordless): (Vec<usize>, Vec<usize>) =
+            (0..rules.len()).partition(|&i| !rules[i].keywords.is_empty());
+        let keywords: Vec<&str> = rules.iter().flat_map(|r| r.keywords.clone()).collect();
+        let prefilter = if keywords.is_empty() {
+            None
+        } else {
+            AhoCorasickBuilder::new()
+                .ascii_case_insensitive(true)
+                .match_kind(MatchKind::LeftmostFirst)
+                .build(&keywords)
+                .ok()
+        };
+
+        Ok(Self {
+            filtered: RuleGroup::new(&rules, keyworded)?,
+            unfiltered: RuleGroup::new(&rules, keywordless)?,
+            regexes,
+            prefilter,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.regexes.len()
+    }
+
+    /// Cheap check whether any keyworded rule could possibly match
+    fn may_filter(&self, text: &str) -> bool {
+        // Without a prefilter (it failed to build), run every rule
+        !self.filtered.indices.is_empty()
+            && self.prefilter.as_ref().is_none_or(|ac| ac.is_match(text))
+    }
+
+    /// Index of the first rule (in declaration order) matching `text`
+    pub fn first_match(&self, text: &str) -> Option<usize> {
+        let unfiltered = self.unfiltered.matches(text).next();
+        let filtered = self
+            .may_filter(text)
+            .then(|| self.filtered.matches(text).next());
+        unfiltered.into_iter().chain(filtered.flatten()).min()
+    }
+
+    /// Indices of every rule matching `text`, in declaration order
+    pub fn matches(&self, text: &str) -> Vec<usize> {
+        let mut found: Vec<usize> = self.unfiltered.matches(text).collect();
+        if self.may_filter(text) {
+            found.extend(self.filtered.matches(text));
+            found.sort_unstable();
+        }
+        found
+    }
+
+    pub fn regex(&self, index: usize) -> &Regex {
+        &self.regexes[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_match_in_declaration_order() {
+        let rules = RuleSet::new([
+            Rule {
+                pattern: r"ghp_[A-Za-z0-9]{4}",
+                keywords: vec!["ghp_"],
+            },
+            Rule {
+                pattern: r"token\s*=",
+                keywords: vec!["token"],
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(rules.first_match("token = ghp_abcd"), Some(0));
+        assert_eq!(rules.first_match("TOKEN = 1"), None);
+        assert_eq!(rules.first_match("token = 1"), Some(1));
+        assert_eq!(rules.matches("token = ghp_abcd"), vec![0, 1]);
+        assert_eq!(rules.first_match("nothing to see"), None);
+    }
+
+    #[test]
+    fn test_rules_without_keywords_always_run() {
+        let rules = RuleSet::new([Rule {
+            pattern: r"\d{3}",
+            keywords: vec![],
+        }])
+        .unwrap();
+        assert_eq!(rules.first_match("abc 123"), Some(0));
+
+        // Keyworded rules stay behind the prefilter
+        let rules = RuleSet::new([
+            Rule {
+                pattern: r"[a-z]+_\d+",
+                keywords: vec!["tok_"],
+            },
+            Rule {
+                pattern: r"\d{3}",
+                keywords: vec![],
+            },
+        ])
+        .unwrap();
+        assert_eq!(rules.matches("abc_123"), vec![1]);
+        assert_eq!(rules.matches("tok_123"), vec![0, 1]);
+        assert_eq!(rules.first_match("tok_123"), Some(0));
+    }
+}
@@ -0,0 +1,562 @@
+use crate::config::EnvConfig;
+use crate::secrets::env_pairs;
+use crate::{CheckResult, Severity, Violation};
+use anyhow::{Context, Result};
+use colored::*;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Name fragments marking a variable that must never reach the browser
+const SENSITIVE_NAME_MARKERS: &[&str] = &[
+    "SERVICE_ROLE",
+    "SECRET",
+    "SIGNING_KEY",
+    "PRIVATE_KEY",
+    "PASSWORD",
+];
+
+/// Prefix Next.js inlines into client bundles
+pub const PUBLIC_PREFIX: &str = "NEXT_PUBLIC_";
+
+/// Documented values that are instructions rather than usable defaults
+const PLACEHOLDER_PATTERN: &str =
+    r"(?i)(your[_-]|^generate_|\.\.\.$|^<.*>$|changeme|replace[_-]?me|^sig_$)";
+
+/// `process.env.X` and `process.env["X"]`
+const ENV_REFERENCE_PATTERN: &str =
+    r#"process\.env(?:\.([A-Za-z_][A-Za-z0-9_]*)|\[\s*["'`]([A-Za-z_][A-Za-z0-9_]*)["'`]\s*\])"#;
+
+/// Variables provided by Node.js or Next.js that are never documented
+const RUNTIME_VARIABLES: &[&str] = &["NODE_ENV", "NEXT_RUNTIME", "CI"];
+
+/// Source extensions searched for env references
+const SOURCE_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// Commented-out assignments document optional variables
+const COMMENTED_KEY_PATTERN: &str = r"^#\s*([A-Z][A-Z0-9_]*)=(.*)$";
+
+/// Length requirements stated in comments, e.g. "(minimum 32 characters)"
+const MIN_LENGTH_HINT_PATTERN: &str = r"(?i)(?:minimum|at least)\s+(\d+)\s+char";
+
+/// A variable documented in the example env file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Documented {
+    pub value: String,
+    /// Active assignments are required, commented-out ones optional
+    pub required: bool,
+    pub min_length: Option<usize>,
+    pub line: usize,
+}
+
+/// Parse the example env file, including optional commented-out keys
+pub fn parse_example(content: &str) -> BTreeMap<String, Documented> {
+    let commented = Regex::new(COMMENTED_KEY_PATTERN).unwrap();
+    let hint = Regex::new(MIN_LENGTH_HINT_PATTERN).unwrap();
+
+    let mut documented = BTreeMap::new();
+    let mut hints: HashMap<usize, usize> = HashMap::new();
+    let mut pending_hint = None;
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            pending_hint = None;
+        } else if let Some(caps) = commented.captures(line) {
+            documented.insert(
+                caps[1].to_string(),
+                Documented {
+                    value: caps[2].trim().to_string(),
+                    required: false,
+                    min_length: pending_hint.take(),
+                    line: i + 1,
+                },
+            );
+        } else if line.starts_with('#') {
+            if let Some(caps) = hint.captures(line) {
+                pending_hint = caps[1].parse().ok();
+            }
+        } else if let Some(min) = pending_hint.take() {
+            hints.insert(i + 1, min);
+        }
+    }
+
+    for entry in env_pairs(content) {
+        documented.insert(
+            entry.key,
+            Documented {
+                value: entry.value,
+                required: true,
+                min_length: hints.get(&entry.line).copied(),
+                line: entry.line,
+            },
+        );
+    }
+
+    documented
+}
+
+/// Whether a variable name suggests a server-only credential
+pub fn is_sensitive_name(name: &str) -> bool {
+    let name = name.to_uppercase();
+    SENSITIVE_NAME_MARKERS.iter().any(|m| name.contains(m))
+}
+
+/// Compare the local env file with the documented example
+pub fn check_env(
+    file: Option<&str>,
+    example: Option<&str>,
+    config: &EnvConfig,
+) -> Result<CheckResult> {
+    let mut result = CheckResult::new("env-check");
+    let file = file.unwrap_or(&config.file);
+    let example = example.unwrap_or(&config.example);
+
+    let example_content =
+        fs::read_to_string(example).with_context(|| format!("Failed to read {}", example))?;
+    let documented = parse_example(&example_content);
+
+    // Public names in the example itself would be copied by every developer
+    for (key, doc) in &documented {
+        if let Some(v) = public_misuse(key) {
+            report(&mut result, example, Some(doc.line), v);
+        }
+    }
+
+    if !Path::new(file).exists() {
+        report(
+            &mut result,
+            file,
+            None,
+            (
+                Severity::High,
+                format!(
+                    "{} not found; copy {} and fill in your values",
+                    file, example
+                ),
+            ),
+        );
+        return Ok(result);
+    }
+
+    let content = fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?;
+    let local = env_pairs(&content);
+    let placeholder = Regex::new(PLACEHOLDER_PATTERN).unwrap();
+
+    for (key, doc) in documented.iter().filter(|(_, d)| d.required) {
+        let present = local.iter().any(|e| &e.key == key && !e.value.is_empty());
+        if !present {
+            report(
+                &mut result,
+                file,
+                None,
+                (
+                    Severity::High,
+                    format!(
+                        "Missing required variable {} (documented in {}:{})",
+                        key, example, doc.line
+                    ),
+                ),
+            );
+        }
+    }
+
+    for entry in &local {
+        let line = Some(entry.line);
+
+        if let Some(v) = public_misuse(&entry.key) {
+            report(&mut result, file, line, v);
+        }
+
+        let Some(doc) = documented.get(&entry.key) else {
+            let allowed = config
+                .allowed_prefixes
+                .iter()
+                .any(|p| entry.key.starts_with(p.as_str()));
+            if !allowed {
+                report(
+                    &mut result,
+                    file,
+                    line,
+                    (
+                        Severity::Low,
+                        format!(
+                            "Unknown variable {} (not documented in {})",
+                            entry.key, example
+                        ),
+                    ),
+                );
+            }
+            continue;
+        };
+
+        if entry.value == doc.value && placeholder.is_match(&doc.value) {
+            report(
+                &mut result,
+                file,
+                line,
+                (
+                    Severity::High,
+                    format!("{} still has the documented placeholder value", entry.key),
+                ),
+            );
+            continue;
+        }
+
+        let min_length = config
+            .min_lengths
+            .get(&entry.key)
+            .copied()
+            .or(doc.min_length);
+        let length = entry.value.chars().count();
+        if let Some(min) = min_length.filter(|&min| length > 0 && length < min) {
+            report(
+                &mut result,
+                file,
+                line,
+                (
+                    Severity::High,
+                    format!(
+                        "{} is {} characters; at least {} are required",
+                        entry.key, length, min
+                    ),
+                ),
+            );
+        }
+    }
+
+    if result.passed {
+        println!("{}", format!("✅ {} matches {}", file, example).green());
+    }
+
+    Ok(result)
+}
+
+/// A `process.env` read in application code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvReference {
+    pub name: String,
+    pub file: String,
+    pub line: usize,
+    /// Read from a module with a `"use client"` directive
+    pub client: bool,
+}
+
+/// Find every `process.env` reference in a source file
+pub fn env_references(file: &str, content: &str) -> Vec<EnvReference> {
+    let pattern = Regex::new(ENV_REFERENCE_PATTERN).unwrap();
+    let client = is_client_module(content);
+
+    content
+        .lines()
+        .enumerate()
+        .flat_map(|(i, line)| {
+            pattern
+                .captures_iter(line)
+                .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+                .map(|name| EnvReference {
+                    name: name.as_str().to_string(),
+                    file: file.to_string(),
+                    line: i + 1,
+                    client,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Whether the module starts with a `"use client"` directive
+fn is_client_module(content: &str) -> bool {
+    content
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with("//"))
+        .is_some_and(|l| {
+            let directive = l.trim_end_matches(';');
+            directive == "\"use client\"" || directive == "'use client'"
+        })
+}
+
+/// Compare `process.env` references in the app with the documented example
+pub fn check_env_docs(config: &EnvConfig) -> Result<CheckResult> {
+    let mut result = CheckResult::new("env-docs");
+    let example = &config.example;
+    let content =
+        fs::read_to_string(example).with_context(|| format!("Failed to read {}", example))?;
+    let documented = parse_example(&content);
+
+    let mut references = vec![];
+    for source in &config.sources {
+        let walker = WalkDir::new(source)
+            .into_iter()
+            .filter_entry(|e| e.file_name() != "node_modules");
+        for entry in walker.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let is_source = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| SOURCE_EXTENSIONS.contains(&e));
+            if !entry.file_type().is_file() || !is_source {
+                continue;
+            }
+            if let Ok(content) = fs::read_to_string(path) {
+                let file = path.to_string_lossy();
+                references.extend(env_references(&file, &content));
+            }
+        }
+    }
+
+    report_coverage(&mut result, &references, &documented, config);
+
+    if result.passed {
+        println!(
+            "{}",
+            format!(
+                "✅ All {} env variables are documented in {}",
+                documented.len(),
+                example
+            )
+            .green()
+        );
+    }
+
+    Ok(result)
+}
+
+fn report_coverage(
+    result: &mut CheckResult,
+    references: &[EnvReference],
+    documented: &BTreeMap<String, Documented>,
+    config: &EnvConfig,
+) {
+    let provided = |name: &str| {
+        RUNTIME_VARIABLES.contains(&name)
+            || config
+                .allowed_prefixes
+                .iter()
+                .any(|p| name.starts_with(p.as_str()))
+    };
+
+    // First reference of each undocumented variable
+    let mut undocumented: BTreeMap<&str, &EnvReference> = BTreeMap::new();
+    for reference in references {
+        let name = reference.name.as_str();
+        if !documented.contains_key(name) && !provided(name) {
+            undocumented.entry(name).or_insert(reference);
+        }
+    }
+    for (name, reference) in undocumented {
+        report(
+            result,
+            &reference.file,
+            Some(reference.line),
+            (
+                Severity::Medium,
+                format!(
+                    "{} is read here but not documented in {}",
+                    name, config.example
+                ),
+            ),
+        );
+    }
+
+    for (name, doc) in documented {
+        let used = references.iter().any(|r| &r.name == name);
+        if !used && !config.external.contains(name) {
+            report(
+                result,
+                &config.example,
+                Some(doc.line),
+                (
+                    Severity::Low,
+                    format!("{} is documented but never read", name),
+                ),
+            );
+        }
+    }
+
+    // Next.js only inlines NEXT_PUBLIC_ variables into client bundles
+    for reference in references.iter().filter(|r| r.client) {
+        if !reference.name.starts_with(PUBLIC_PREFIX)
+            && !RUNTIME_VARIABLES.contains(&reference.name.as_str())
+        {
+            report(
+                result,
+                &reference.file,
+                Some(reference.line),
+                (
+                    Severity::High,
+                    format!(
+                        "Server-only variable {} read in a \"use client\" module",
+                        reference.name
+                    ),
+                ),
+            );
+        }
+    }
+}
+
+/// Server-only names exposed to the browser through the public prefix
+fn public_misuse(key: &str) -> Option<(Severity, String)> {
+    let name = key.strip_prefix(PUBLIC_PREFIX)?;
+    is_sensitive_name(name).then(|| {
+        (
+            Severity::Critical,
+            format!(
+                "{} exposes a server-only value to the browser; drop the {} prefix",
+                key, PUBLIC_PREFIX
+            ),
+        )
+    })
+}
+
+fn report(
+    result: &mut CheckResult,
+    file: &str,
+    line: Option<usize>,
+    (severity, message): (Severity, String),
+) {
+    let location = match line {
+        Some(line) => format!("{}:{}", file, line),
+        None => file.to_string(),
+    };
+    println!("{} {}: {}", "❌".red(), location, message);
+    result.add_violation(Violation {
+        file: file.to_string(),
+        line,
+        message,
+        severity: Some(severity),
+        ..Default::default()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const EXAMPLE: &str = "\
+# Supabase service role key
+SUPABASE_SERVICE_ROLE_KEY=eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...
+
+# Generate a secure random string (minimum 32 characters):
+#   openssl rand -base64 32
+CRON_SECRET=generate_a_secure_random_string_here
+
+NEXT_PUBLIC_SITE_URL=http://localhost:3001
+
+# QSTASH_CURRENT_SIGNING_KEY=sig_...
+";
+
+    fn run(local: &str) -> CheckResult {
+        // Tests run in parallel, so each call gets its own directory
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "audit-env-{}-{}",
+            std::process::id(),
+            RUNS.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let example = dir.join("example");
+        let file = dir.join("local");
+        fs::write(&example, EXAMPLE).unwrap();
+        fs::write(&file, local).unwrap();
+        let result = check_env(
+            file.to_str(),
+            example.to_str(),
+            &EnvConfig {
+                min_lengths: BTreeMap::new(),
+                ..Default::default()
+            },
+        );
+        let _ = fs::remove_dir_all(&dir);
+        result.unwrap()
+    }
+
+    #[test]
+    fn test_env_references() {
+        let content = "'use client';\n\nconst a = process.env.NEXT_PUBLIC_SITE_URL;\nconst b = process.env['CRON_SECRET'] ?? process.env.NODE_ENV;\n";
+        let refs = env_references("components/a.tsx", content);
+        let names: Vec<&str> = refs.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["NEXT_PUBLIC_SITE_URL", "CRON_SECRET", "NODE_ENV"]
+        );
+        assert!(refs.iter().all(|r| r.client));
+        assert_eq!(refs[1].line, 4);
+
+        assert!(
+            !env_references("lib/a.ts", "const a = process.env.X;\n// 'use client'\n")[0].client
+        );
+    }
+
+    #[test]
+    fn test_env_docs_coverage() {
+        let mut refs = env_references(
+            "app/route.ts",
+            "process.env.CRON_SECRET;\nprocess.env.QSTASH_TOKEN;\nprocess.env.VERCEL_URL;\n",
+        );
+        refs.extend(env_references("components/a.tsx", "\"use client\";\nprocess.env.SUPABASE_SERVICE_ROLE_KEY;\nprocess.env.NEXT_PUBLIC_SITE_URL;\n"));
+
+        let mut result = CheckResult::new("env-docs");
+        report_coverage(
+            &mut result,
+            &refs,
+            &parse_example(EXAMPLE),
+            &EnvConfig::default(),
+        );
+        let messages: Vec<&str> = result
+            .violations
+            .iter()
+            .map(|v| v.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "QSTASH_TOKEN is read here but not documented in .env.local.example",
+                "QSTASH_CURRENT_SIGNING_KEY is documented but never read",
+                "Server-only variable SUPABASE_SERVICE_ROLE_KEY read in a \"use client\" module",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_example() {
+        let documented = parse_example(EXAMPLE);
+        assert!(documented["SUPABASE_SERVICE_ROLE_KEY"].required);
+        assert_eq!(documented["CRON_SECRET"].min_length, Some(32));
+        assert_eq!(documented["NEXT_PUBLIC_SITE_URL"].min_length, None);
+        assert!(!documented["QSTASH_CURRENT_SIGNING_KEY"].required);
+    }
+
+    #[test]
+    fn test_env_check_issues() {
+        let result = run("\
+NEXT_PUBLIC_SITE_URL=http://localhost:3001
+CRON_SECRET=short
+NEXT_PUBLIC_SUPABASE_SERVICE_ROLE_KEY=abc
+QSTASH_CURRENT_SIGNING_KEY=sig_real
+VERCEL_OIDC_TOKEN=x
+");
+        let messages: Vec<&str> = result
+            .violations
+            .iter()
+            .map(|v| v.message.as_str())
+            .collect();
+        assert_eq!(messages.len(), 4, "{:?}", messages);
+        assert!(messages[0].starts_with("Missing required variable SUPABASE_SERVICE_ROLE_KEY"));
+        assert!(messages[1].starts_with("CRON_SECRET is 5 characters; at least 32"));
+        assert!(messages[2].contains("exposes a server-only value"));
+        assert!(messages[3].starts_with("Unknown variable NEXT_PUBLIC_SUPABASE_SERVICE_ROLE_KEY"));
+
+        let result = run("\
+SUPABASE_SERVICE_ROLE_KEY=eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...
+CRON_SECRET=0123456789abcdef0123456789abcdef
+NEXT_PUBLIC_SITE_URL=http://localhost:3001
+");
+        assert_eq!(result.violations.len(), 1);
+        assert!(result.violations[0].message.contains("placeholder"));
+    }
+}
@@ -0,0 +1,508 @@
+use crate::config::Config;
+use crate::prepush;
+use crate::secrets::{Finding, SecretScanner};
+use crate::{should_skip, CheckResult, Violation};
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Marks the start of a commit header in the `git log` stream
+const COMMIT_MARKER: char = '\x1e';
+const FIELD_SEPARATOR: char = '\x1f';
+
+/// Commit metadata attached to history findings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub author: String,
+    pub date: String,
+}
+
+/// A line added to a file by a commit
+#[derive(Debug, PartialEq, Eq)]
+pub struct AddedLine<'a> {
+    pub path: &'a str,
+    pub line: usize,
+    pub text: &'a str,
+}
+
+/// Content a commit adds, as reported by [`PatchParser`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Added<'a> {
+    Line(AddedLine<'a>),
+    /// A binary file; git prints no lines for it, only its new blob id
+    Binary {
+        path: &'a str,
+        blob: &'a str,
+    },
+}
+
+/// Incremental parser for `git log -p --full-index` output
+#[derive(Default)]
+pub struct PatchParser {
+    commit: CommitInfo,
+    path: Option<String>,
+    /// Path from the `diff --git` header, for binary files without `+++`
+    header_path: Option<String>,
+    blob: Option<String>,
+    next_line: usize,
+    in_hunk: bool,
+}
+
+impl PatchParser {
+    /// Feed one line of output, calling `on_added` for every added line or binary file
+    pub fn feed(&mut self, line: &str, mut on_added: impl FnMut(&CommitInfo, Added)) {
+        if let Some(header) = line.strip_prefix(COMMIT_MARKER) {
+            let mut fields = header.split(FIELD_SEPARATOR);
+            self.commit = CommitInfo {
+                sha: fields.next().unwrap_or_default().to_string(),
+                author: fields.next().unwrap_or_default().to_string(),
+                date: fields.next().unwrap_or_default().to_string(),
+            };
+            self.path = None;
+            self.in_hunk = false;
+        } else if let Some(paths) = line.strip_prefix("diff --git ") {
+            // `a/<path> b/<path>`: without renames both halves are the same length
+            self.header_path = paths
+                .get(paths.len().div_ceil(2)..)
+                .and_then(|new| unquote(new).strip_prefix("b/").map(str::to_string));
+            self.path = None;
+            self.blob = None;
+            self.in_hunk = false;
+        } else if let (false, Some(index)) = (self.in_hunk, line.strip_prefix("index ")) {
+            // index <old>..<new> [mode]
+            self.blob = index
+                .split_whitespace()
+                .next()
+                .and_then(|ids| ids.split_once(".."))
+                .map(|(_, new)| new.to_string())
+                .filter(|new| !new.chars().all(|c| c == '0'));
+        } else if let (false, true) = (self.in_hunk, line.starts_with("Binary files ")) {
+            if let (Some(path), Some(blob)) = (&self.header_path, &self.blob) {
+                on_added(&self.commit, Added::Binary { path, blob });
+            }
+        } else if let (false, Some(path)) = (self.in_hunk, line.strip_prefix("+++ ")) {
+            self.path = unquote(path).strip_prefix("b/").map(str::to_string);
+        } else if let Some(hunk) = line.strip_prefix("@@ ") {
+            // @@ -a,b +c,d @@
+            self.in_hunk = true;
+            self.next_line = hunk
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('+'))
+                .and_then(|range| range.split(',').next())
+                .and_then(|start| start.parse().ok())
+                .unwrap_or(1);
+        } else if let Some(text) = line.strip_prefix('+') {
+            if let Some(path) = &self.path {
+                on_added(
+                    &self.commit,
+                    Added::Line(AddedLine {
+                        path,
+                        line: self.next_line,
+                        text,
+                    }),
+                );
+            }
+            self.next_line += 1;
+        } else if line.starts_with(' ') {
+            self.next_line += 1;
+        }
+    }
+}
+
+/// Undo git's C-style quoting of unusual paths, e.g. `"b/caf\303\251.txt"`
+fn unquote(path: &str) -> String {
+    let Some(inner) = path.strip_prefix('"').and_then(|p| p.strip_suffix('"')) else {
+        return path.to_string();
+    };
+    let mut bytes = vec![];
+    let mut chars = inner.bytes().peekable();
+    while let Some(b) = chars.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        match chars.next() {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(b'a') => bytes.push(0x07),
+            Some(b'b') => bytes.push(0x08),
+            Some(b'f') => bytes.push(0x0c),
+            Some(b'v') => bytes.push(0x0b),
+            Some(d @ b'0'..=b'7') => {
+                let mut value = u32::from(d - b'0');
+                for _ in 0..2 {
+                    if let Some(d) = chars.next_if(|d| (b'0'..=b'7').contains(d)) {
+                        value = value * 8 + u32::from(d - b'0');
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => bytes.push(other),
+            None => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// A secret seen in history, keyed by detector and matched text
+struct HistoryFinding {
+    violation: Violation,
+    occurrences: usize,
+}
+
+/// Findings in the order they were introduced, with repeats counted
+#[derive(Default)]
+struct Findings {
+    seen: HashMap<(String, String), HistoryFinding>,
+    order: Vec<(String, String)>,
+}
+
+impl Findings {
+    /// Record findings in a binary blob, which has no lines to compare
+    fn record_decoded(&mut self, commit: &CommitInfo, decoded: Vec<(String, Finding)>) {
+        for (entry, finding) in decoded {
+            // Short secrets share a `****` preview; key on the value itself
+            let evidence = match finding.fingerprint {
+                Some(hash) => format!("{:016x}", hash),
+                None => format!("{}:{}", entry, finding.line),
+            };
+            self.record(commit, &entry, finding, evidence);
+        }
+    }
+
+    /// Record a finding; `evidence` identifies the same secret in later commits
+    fn record(&mut self, commit: &CommitInfo, path: &str, finding: Finding, evidence: String) {
+        let key = (finding.rule.clone(), evidence);
+        if let Some(existing) = self.seen.get_mut(&key) {
+            existing.occurrences += 1;
+            return;
+        }
+        self.order.push(key.clone());
+        self.seen.insert(
+            key,
+            HistoryFinding {
+                violation: Violation {
+                    file: path.to_string(),
+                    line: Some(finding.line),
+                    message: finding.message,
+                    pattern: finding.pattern,
+                    rule: Some(finding.rule),
+                    provider: Some(finding.provider),
+                    severity: Some(finding.severity),
+                    preview: finding.preview,
+                    commit: Some(commit.sha.clone()),
+                    author: Some(commit.author.clone()),
+                    date: Some(commit.date.clone()),
+                },
+                occurrences: 1,
+            },
+        );
+    }
+}
+
+/// Consecutive lines a commit added to one file
+///
+/// Scanned as a unit with the full scanner, so secrets spanning lines (PEM
+/// blocks, concatenations, values on the next line) and encoded ones are
+/// found just as in the working tree.
+#[derive(Default)]
+struct AddedHunk {
+    commit: CommitInfo,
+    path: String,
+    start: usize,
+    lines: Vec<String>,
+}
+
+impl AddedHunk {
+    fn push(&mut self, commit: &CommitInfo, added: &AddedLine) {
+        if self.lines.is_empty() {
+            self.commit = commit.clone();
+            self.path = added.path.to_string();
+            self.start = added.line;
+        }
+        self.lines.push(added.text.to_string());
+    }
+
+    fn scan(&mut self, scanner: &SecretScanner, findings: &mut Findings) {
+        if self.lines.is_empty() {
+            return;
+        }
+        let text = self.lines.join("\n");
+        for mut finding in scanner.scan(&self.path, &text) {
+            let evidence = self
+                .lines
+                .get(finding.line.saturating_sub(1))
+                .map_or_else(String::new, |l| l.trim().to_string());
+            finding.line += self.start - 1;
+            findings.record(&self.commit, &self.path, finding, evidence);
+        }
+        self.lines.clear();
+    }
+
+    /// Whether `added` is the next line of this hunk
+    fn continued_by(&self, commit: &CommitInfo, added: &AddedLine) -> bool {
+        self.commit.sha == commit.sha
+            && self.path == added.path
+            && self.start + self.lines.len() == added.line
+    }
+}
+
+/// Run the secret scanner over every hunk and binary file ever added to the repository
+pub fn check_secrets_history(since: Option<&str>, config: &Config) -> Result<CheckResult> {
+    let mut result = CheckResult::new("secrets-history");
+    let scanner = SecretScanner::new(&config.secrets);
+
+    let format = format!(
+        "--format={}%H{}%an <%ae>{}%aI",
+        COMMIT_MARKER, FIELD_SEPARATOR, FIELD_SEPARATOR
+    );
+    let range = match since {
+        Some(rev) => format!("{}..HEAD", rev),
+        None => "HEAD".to_string(),
+    };
+
+    let mut child = Command::new("git")
+        .args([
+            "log",
+            "-p",
+            "--reverse",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-renames",
+            "--full-index",
+            "-U0",
+            &format,
+            &range,
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to run git log")?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .context("Failed to read git log output")?;
+    let mut reader = BufReader::new(stdout);
+
+    // Oldest commit first, so the first sighting is where a secret was introduced
+    let mut findings = Findings::default();
+    let mut hunk = AddedHunk::default();
+    let mut parser = PatchParser::default();
+    let mut buf = vec![];
+
+    while reader.read_until(b'\n', &mut buf)? > 0 {
+        let line = String::from_utf8_lossy(&buf);
+        parser.feed(
+            line.trim_end_matches(['\n', '\r']),
+            |commit, added| match added {
+                Added::Line(added) => {
+                    if should_skip(added.path, config) {
+                        return;
+                    }
+                    if !hunk.continued_by(commit, &added) {
+                        hunk.scan(&scanner, &mut findings);
+                    }
+                    hunk.push(commit, &added);
+                }
+                Added::Binary { path, blob } => {
+                    if should_skip(path, config) {
+                        return;
+                    }
+                    hunk.scan(&scanner, &mut findings);
+                    let Ok(bytes) = prepush::read_blob(Path::new("."), blob) else {
+                        return;
+                    };
+                    findings.record_decoded(
+                        commit,
+                        scanner.scan_decoded(path, &bytes, &config.secrets.decode),
+                    );
+                }
+            },
+        );
+        buf.clear();
+    }
+    hunk.scan(&scanner, &mut findings);
+
+    let status = child.wait().context("Failed to wait for git log")?;
+    if !status.success() {
+        bail!("git log failed for range {}", range);
+    }
+
+    for key in findings.order {
+        let Some(mut finding) = findings.seen.remove(&key) else {
+            continue;
+        };
+        let v = &mut finding.violation;
+        if finding.occurrences > 1 {
+            v.message = format!("{} (added in {} commits)", v.message, finding.occurrences);
+        }
+
+        println!(
+            "{} {}:{}",
+            "🔐 Potential secret in history:".red(),
+            v.file,
+            v.line.unwrap_or(0)
+        );
+        println!(
+            "   Commit: {} by {} on {}",
+            short_sha(v.commit.as_deref().unwrap_or_default()),
+            v.author.as_deref().unwrap_or_default(),
+            v.date.as_deref().unwrap_or_default()
+        );
+        println!(
+            "   Detector: {} ({})",
+            v.rule.as_deref().unwrap_or_default(),
+            v.provider.as_deref().unwrap_or_default()
+        );
+        if let Some(preview) = &v.preview {
+            println!("   Secret: {}", preview);
+        }
+        result.add_violation(finding.violation);
+    }
+
+    if result.passed {
+        println!("{}", "✅ No secrets found in history".green());
+    }
+
+    Ok(result)
+}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SecretsConfig;
+
+    #[test]
+    fn test_patch_parser_line_numbers() {
+        let log = "\x1eabc123\x1fJane <jane@example.com>\x1f2025-01-01T00:00:00+00:00\n\
+diff --git a/lib/a.ts b/lib/a.ts\n\
+--- a/lib/a.ts\n\
++++ b/lib/a.ts\n\
+@@ -10,0 +11,2 @@ export function a() {\n\
++const one = 1;\n\
++const two = 2;\n\
+diff --git a/old.ts b/old.ts\n\
+--- a/old.ts\n\
++++ /dev/null\n\
+@@ -1 +0,0 @@\n\
+-removed\n\
+diff --git \"a/caf\\303\\251 menu.ts\" \"b/caf\\303\\251 menu.ts\"\n\
+--- /dev/null\n\
++++ \"b/caf\\303\\251 menu.ts\"\n\
+@@ -0,0 +1 @@\n\
++const menu = [];\n\
+diff --git a/public/logo.png b/public/logo.png\n\
+new file mode 100644\n\
+index 0000000000000000000000000000000000000000..89abcdef89abcdef89abcdef89abcdef89abcdef\n\
+Binary files /dev/null and b/public/logo.png differ\n";
+
+        let mut parser = PatchParser::default();
+        let mut added = vec![];
+        for line in log.lines() {
+            parser.feed(line, |commit, added_content| match added_content {
+                Added::Line(line) => added.push((
+                    commit.sha.clone(),
+                    line.path.to_string(),
+                    line.line,
+                    line.text.to_string(),
+                )),
+                Added::Binary { path, blob } => {
+                    added.push((commit.sha.clone(), path.to_string(), 0, blob.to_string()))
+                }
+            });
+        }
+
+        assert_eq!(
+            added,
+            vec![
+                (
+                    "abc123".to_string(),
+                    "lib/a.ts".to_string(),
+                    11,
+                    "const one = 1;".to_string()
+                ),
+                (
+                    "abc123".to_string(),
+                    "lib/a.ts".to_string(),
+                    12,
+                    "const two = 2;".to_string()
+                ),
+                (
+                    "abc123".to_string(),
+                    "café menu.ts".to_string(),
+                    1,
+                    "const menu = [];".to_string()
+                ),
+                (
+                    "abc123".to_string(),
+                    "public/logo.png".to_string(),
+                    0,
+                    "89abcdef89abcdef89abcdef89abcdef89abcdef".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hunks_scanned_as_a_whole() {
+        let scanner = SecretScanner::new(&Default::default());
+        let commit = CommitInfo {
+            sha: "abc123".to_string(),
+            ..Default::default()
+        };
+        let mut hunk = AddedHunk::default();
+        let mut findings = Findings::default();
+        // Only the joined string is long enough to be a Supabase token
+        for (line, text) in [
+            (7, "const key = \"sbp_0123456789abcdef\" +"),
+            (8, "  \"0123456789abcdef0123\";"),
+        ] {
+            let added = AddedLine {
+                path: "lib/client.ts",
+                line,
+                text,
+            };
+            assert!(hunk.lines.is_empty() || hunk.continued_by(&commit, &added));
+            hunk.push(&commit, &added);
+        }
+        hunk.scan(&scanner, &mut findings);
+
+        assert_eq!(findings.order.len(), 1);
+        let finding = &findings.seen[&findings.order[0]].violation;
+        assert_eq!(finding.line, Some(7));
+        assert_eq!(finding.commit.as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn test_binary_findings_keyed_by_value() {
+        let config = SecretsConfig {
+            extra_patterns: vec!["tok_[a-z]{6}".to_string()],
+            ..Default::default()
+        };
+        let scanner = SecretScanner::new(&config);
+        let commit = CommitInfo::default();
+        let mut findings = Findings::default();
+        // Both previews are `****`, the same token in a later blob is a repeat
+        for bytes in [&b"a tok_abcdef\nb tok_ghijkl\n"[..], b"c tok_abcdef\n"] {
+            let decoded = scanner.scan_decoded("data.bin", bytes, &config.decode);
+            findings.record_decoded(&commit, decoded);
+        }
+
+        assert_eq!(findings.order.len(), 2);
+        let occurrences: Vec<usize> = findings
+            .order
+            .iter()
+            .map(|key| findings.seen[key].occurrences)
+            .collect();
+        assert_eq!(occurrences, vec![2, 1]);
+    }
+}
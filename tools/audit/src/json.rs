@@ -0,0 +1,333 @@
+use anyhow::{bail, Result};
+
+/// A JSON value with the byte span it was parsed from
+///
+/// serde_json drops source positions, which edits that must preserve the
+/// rest of a file (version bumps) and line-accurate reports need.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub value: Value,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Node>),
+    /// Members in source order
+    Object(Vec<(String, Node)>),
+}
+
+impl Node {
+    /// Member of an object by key
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        match &self.value {
+            Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Resolve an RFC 6901 JSON pointer such as `/packages//version`
+    pub fn pointer(&self, pointer: &str) -> Option<&Node> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .try_fold(self, |node, token| match &node.value {
+                Value::Array(items) => items.get(token.parse::<usize>().ok()?),
+                _ => node.get(&token),
+            })
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// 1-based line and column of a byte offset
+pub fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before.len(), |i| before.len() - i - 1)
+        + 1;
+    (line, column)
+}
+
+/// Parse a complete JSON document
+pub fn parse(text: &str) -> Result<Node> {
+    let mut parser = Parser {
+        text,
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    let node = parser.value()?;
+    parser.whitespace();
+    if parser.pos < parser.bytes.len() {
+        return parser.error("Trailing characters after JSON value");
+    }
+    Ok(node)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error<T>(&self, message: &str) -> Result<T> {
+        let (line, column) = line_col(self.text, self.pos);
+        bail!("{} at line {} column {}", message, line, column)
+    }
+
+    fn whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        self.whitespace();
+        if self.bytes.get(self.pos) != Some(&byte) {
+            return self.error(&format!("Expected '{}'", byte as char));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Node> {
+        self.whitespace();
+        let start = self.pos;
+        let value = match self.bytes.get(self.pos) {
+            Some(b'{') => self.object()?,
+            Some(b'[') => self.array()?,
+            Some(b'"') => Value::String(self.string()?),
+            Some(b't') => self.literal("true", Value::Bool(true))?,
+            Some(b'f') => self.literal("false", Value::Bool(false))?,
+            Some(b'n') => self.literal("null", Value::Null)?,
+            Some(b'-' | b'0'..=b'9') => self.number()?,
+            Some(_) => return self.error("Unexpected character"),
+            None => return self.error("Unexpected end of input"),
+        };
+        Ok(Node {
+            value,
+            start,
+            end: self.pos,
+        })
+    }
+
+    fn object(&mut self) -> Result<Value> {
+        self.pos += 1;
+        let mut members = vec![];
+        self.whitespace();
+        if self.bytes.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.whitespace();
+            if self.bytes.get(self.pos) != Some(&b'"') {
+                return self.error("Expected object key");
+            }
+            let key = self.string()?;
+            self.expect(b':')?;
+            members.push((key, self.value()?));
+            self.whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => return self.error("Expected ',' or '}'"),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value> {
+        self.pos += 1;
+        let mut items = vec![];
+        self.whitespace();
+        if self.bytes.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                _ => return self.error("Expected ',' or ']'"),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let rest = &self.text[self.pos..];
+            let Some(c) = rest.chars().next() else {
+                return self.error("Unterminated string");
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(out),
+                '\\' => out.push(self.escape()?),
+                c if (c as u32) < 0x20 => return self.error("Control character in string"),
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char> {
+        let Some(&b) = self.bytes.get(self.pos) else {
+            return self.error("Unterminated string");
+        };
+        self.pos += 1;
+        Ok(match b {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex4()?;
+                if (0xd800..0xdc00).contains(&high) && self.text[self.pos..].starts_with("\\u") {
+                    self.pos += 2;
+                    let low = self.hex4()?;
+                    let code =
+                        0x10000 + ((high - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                    char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                } else {
+                    char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER)
+                }
+            }
+            _ => {
+                self.pos -= 1;
+                return self.error("Invalid escape");
+            }
+        })
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self.text.get(self.pos..self.pos + 4);
+        match digits.and_then(|d| u32::from_str_radix(d, 16).ok()) {
+            Some(value) => {
+                self.pos += 4;
+                Ok(value)
+            }
+            None => self.error("Invalid unicode escape"),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value> {
+        if !self.text[self.pos..].starts_with(word) {
+            return self.error("Unexpected character");
+        }
+        self.pos += word.len();
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| matches!(b, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+        {
+            self.pos += 1;
+        }
+        let raw = &self.text[start..self.pos];
+        match raw.parse::<f64>() {
+            Ok(n) if is_json_number(raw) => Ok(Value::Number(n)),
+            _ => {
+                self.pos = start;
+                self.error("Invalid number")
+            }
+        }
+    }
+}
+
+/// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`, stricter than `f64::from_str`
+fn is_json_number(raw: &str) -> bool {
+    fn digits(s: &str) -> (&str, &str) {
+        let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        s.split_at(end)
+    }
+
+    let (int, rest) = digits(raw.strip_prefix('-').unwrap_or(raw));
+    if int.is_empty() || (int.len() > 1 && int.starts_with('0')) {
+        return false;
+    }
+    let rest = match rest.strip_prefix('.') {
+        Some(fraction) => match digits(fraction) {
+            ("", _) => return false,
+            (_, rest) => rest,
+        },
+        None => rest,
+    };
+    match rest.strip_prefix(['e', 'E']) {
+        Some(exponent) => {
+            let (exp, rest) = digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent));
+            !exp.is_empty() && rest.is_empty()
+        }
+        None => rest.is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_with_spans_and_pointers() {
+        let text = "{\n  \"name\": \"uptime\",\n  \"packages\": {\n    \"\": { \"version\": \"0.1.0\" }\n  },\n  \"list\": [1, -2.5e3, true, null, \"\\u00e9\\ud83d\\ude00\"]\n}";
+        let root = parse(text).unwrap();
+        let version = root.pointer("/packages//version").unwrap();
+        assert_eq!(version.as_str(), Some("0.1.0"));
+        assert_eq!(&text[version.start..version.end], "\"0.1.0\"");
+        assert_eq!(line_col(text, version.start), (4, 22));
+        assert_eq!(
+            root.pointer("/list/1").map(|n| &n.value),
+            Some(&Value::Number(-2500.0))
+        );
+        assert_eq!(root.pointer("/list/4").unwrap().as_str(), Some("é😀"));
+        assert!(root.pointer("/missing").is_none());
+    }
+
+    #[test]
+    fn test_parse_errors_have_positions() {
+        let err = parse("{\n  \"a\": 1,\n}").unwrap_err().to_string();
+        assert_eq!(err, "Expected object key at line 3 column 1");
+        assert!(parse("[01]").is_err());
+        assert!(parse("[1.]").is_err());
+        assert!(parse("[1e+]").is_err());
+        assert!(parse("[-0.5E-3]").is_ok());
+        assert!(parse("{} x").is_err());
+        assert!(parse("\"abc").is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use crate::Severity;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use regex::Regex;
+use serde_json::Value;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+const JWT_PATTERN: &str = r"eyJ[A-Za-z0-9_-]{8,}\.eyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]*";
+
+/// Variable names under which a Supabase anon key is expected to live
+const ANON_KEY_CONTEXT: &str = r"(?i)NEXT_PUBLIC_SUPABASE_ANON_KEY|anon[_-]?key";
+
+/// Decoded (but unverified) JWT header and payload
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jwt {
+    pub header: Value,
+    pub payload: Value,
+}
+
+impl Jwt {
+    /// Decode a compact JWT without verifying its signature
+    pub fn decode(token: &str) -> Option<Self> {
+        let mut parts = token.split('.');
+        let header = decode_part(parts.next()?)?;
+        let payload = decode_part(parts.next()?)?;
+        parts.next()?;
+        Some(Self { header, payload })
+    }
+
+    fn claim(&self, name: &str) -> Option<&str> {
+        self.payload.get(name).and_then(Value::as_str)
+    }
+
+    pub fn role(&self) -> Option<&str> {
+        self.claim("role")
+    }
+
+    /// Supabase project ref, either as a claim or from the issuer URL
+    pub fn project_ref(&self) -> Option<&str> {
+        self.claim("ref").or_else(|| {
+            self.claim("iss")?
+                .strip_prefix("https://")?
+                .strip_suffix(".supabase.co/auth/v1")
+        })
+    }
+
+    pub fn exp(&self) -> Option<i64> {
+        self.payload.get("exp").and_then(Value::as_i64)
+    }
+
+    pub fn is_supabase(&self) -> bool {
+        self.claim("iss")
+            .is_some_and(|iss| iss.starts_with("supabase") || iss.contains(".supabase.co"))
+            || self.claim("ref").is_some()
+    }
+}
+
+fn decode_part(part: &str) -> Option<Value> {
+    let bytes = URL_SAFE_NO_PAD.decode(part.trim_end_matches('=')).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Classification of a JWT found in source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtClassification {
+    pub rule: &'static str,
+    pub provider: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+fn jwt_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(JWT_PATTERN).unwrap())
+}
+
+/// Find the first decodable JWT in a line
+pub fn find(line: &str) -> Option<(&str, Jwt)> {
+    jwt_regex()
+        .find_iter(line)
+        .find_map(|m| Jwt::decode(m.as_str()).map(|jwt| (m.as_str(), jwt)))
+}
+
+/// Byte ranges of everything shaped like a JWT, decodable or not
+pub fn spans(text: &str) -> Vec<Range<usize>> {
+    jwt_regex().find_iter(text).map(|m| m.range()).collect()
+}
+
+/// Classify a JWT by its Supabase `role` claim and the line it appears on
+pub fn classify(jwt: &Jwt, context: &str) -> JwtClassification {
+    static ANON: OnceLock<Regex> = OnceLock::new();
+    let anon_context = ANON.get_or_init(|| Regex::new(ANON_KEY_CONTEXT).unwrap());
+
+    let (rule, severity, what) = match (jwt.is_supabase(), jwt.role()) {
+        (true, Some("service_role")) => (
+            "supabase-service-role-key",
+            Severity::Critical,
+            "Supabase service_role key (bypasses RLS)",
+        ),
+        (true, Some("anon")) if anon_context.is_match(context) => (
+            "supabase-anon-key",
+            Severity::Info,
+            "Supabase anon key (public by design)",
+        ),
+        (true, Some("anon")) => (
+            "supabase-anon-key",
+            Severity::Low,
+            "Supabase anon key outside NEXT_PUBLIC_SUPABASE_ANON_KEY",
+        ),
+        (true, _) => (
+            "supabase-user-jwt",
+            Severity::High,
+            "Supabase user access token",
+        ),
+        (false, _) => ("jwt", Severity::Medium, "JSON Web Token"),
+    };
+
+    let mut details = vec![];
+    if let Some(alg) = jwt.header.get("alg").and_then(Value::as_str) {
+        details.push(format!("alg {}", alg));
+    }
+    if let Some(project) = jwt.project_ref() {
+        details.push(format!("project {}", project));
+    }
+    if let Some(exp) = jwt.exp() {
+        details.push(format_exp(exp));
+    }
+
+    let message = if details.is_empty() {
+        what.to_string()
+    } else {
+        format!("{} ({})", what, details.join(", "))
+    };
+
+    JwtClassification {
+        rule,
+        provider: if jwt.is_supabase() { "supabase" } else { "jwt" },
+        severity,
+        message,
+    }
+}
+
+fn format_exp(exp: i64) -> String {
+    match chrono::DateTime::from_timestamp(exp, 0) {
+        Some(date) if date < chrono::Utc::now() => {
+            format!("expired {}", date.format("%Y-%m-%d"))
+        }
+        Some(date) => format!("expires {}", date.format("%Y-%m-%d")),
+        None => format!("exp {}", exp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(payload: &str) -> String {
+        format!(
+            "{}.{}.c2lnbmF0dXJl",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#),
+            URL_SAFE_NO_PAD.encode(payload)
+        )
+    }
+
+    #[test]
+    fn test_service_role_is_critical() {
+        let jwt = token(
+            r#"{"iss":"supabase","ref":"abcdefghij","role":"service_role","exp":4102444800}"#,
+        );
+        let line = format!("SUPABASE_SERVICE_ROLE_KEY={}", jwt);
+        let (_, decoded) = find(&line).unwrap();
+        let class = classify(&decoded, &line);
+
+        assert_eq!(class.rule, "supabase-service-role-key");
+        assert_eq!(class.severity, Severity::Critical);
+        assert!(class.message.contains("project abcdefghij"));
+        assert!(class.message.contains("expires 2100-01-01"));
+
+        // Default local key from the Supabase CLI
+        let demo = token(r#"{"iss":"supabase-demo","role":"service_role","exp":1983812996}"#);
+        let (_, decoded) = find(&demo).unwrap();
+        let class = classify(&decoded, &demo);
+        assert_eq!(class.rule, "supabase-service-role-key");
+        assert_eq!(class.severity, Severity::Critical);
+    }
+
+    #[test]
+    fn test_anon_key_context() {
+        let jwt = token(r#"{"iss":"supabase","ref":"abcdefghij","role":"anon"}"#);
+        let (_, decoded) = find(&jwt).unwrap();
+
+        let public = format!("NEXT_PUBLIC_SUPABASE_ANON_KEY={}", jwt);
+        assert_eq!(classify(&decoded, &public).severity, Severity::Info);
+
+        let inline = format!("const key = '{}'", jwt);
+        assert_eq!(classify(&decoded, &inline).severity, Severity::Low);
+    }
+
+    #[test]
+    fn test_non_jwt_ignored() {
+        assert!(find("eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...").is_none());
+        assert!(find("eyJub3Rqc29u.eyJub3Rqc29u.sig").is_none());
+    }
+}
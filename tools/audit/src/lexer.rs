@@ -0,0 +1,382 @@
+use std::path::Path;
+
+/// Extensions treated as JavaScript/TypeScript sources
+pub const JS_EXTENSIONS: &[&str] = &["js", "jsx", "ts", "tsx", "mjs", "cjs", "mts", "cts"];
+
+/// Extensions whose sources may contain JSX
+const JSX_EXTENSIONS: &[&str] = &["jsx", "tsx"];
+
+/// Keywords after which `/` starts a regex and `<` may start JSX
+const EXPRESSION_KEYWORDS: &[&str] = &[
+    "return", "typeof", "case", "yield", "await", "void", "in", "of",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Code, with the number of unclosed `{` opened in this context
+    Code {
+        depth: usize,
+    },
+    BlockComment,
+    Template,
+    /// Inside `<tag ...>` or `</tag>`
+    JsxTag {
+        closing: bool,
+    },
+    /// Text between JSX tags
+    JsxChildren,
+}
+
+/// Line-oriented JS/TS lexer that blanks out everything but executable code
+///
+/// String and template literal contents, comments, regex literals and JSX
+/// text are replaced by spaces with the same byte length, so matches on the
+/// masked line keep their columns. `${}` and JSX `{}` expressions stay code.
+pub struct JsLexer {
+    stack: Vec<Mode>,
+    jsx: bool,
+    /// Last significant code character
+    prev: Option<char>,
+    /// Identifier or keyword ending at `prev`
+    prev_word: String,
+}
+
+impl JsLexer {
+    pub fn new(jsx: bool) -> Self {
+        Self {
+            stack: vec![Mode::Code { depth: 0 }],
+            jsx,
+            prev: None,
+            prev_word: String::new(),
+        }
+    }
+
+    /// A lexer for `path` if it is a JS/TS source file
+    pub fn for_path(path: &str) -> Option<Self> {
+        let ext = Path::new(path).extension()?.to_str()?;
+        JS_EXTENSIONS
+            .contains(&ext)
+            .then(|| Self::new(JSX_EXTENSIONS.contains(&ext)))
+    }
+
+    /// Mask one line, carrying comment/template/JSX state to the next
+    pub fn mask_line(&mut self, line: &str) -> String {
+        let chars: Vec<char> = line.chars().collect();
+        let mut out = String::with_capacity(line.len());
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            let mode = *self.stack.last().unwrap_or(&Mode::Code { depth: 0 });
+
+            i = match mode {
+                Mode::Code { depth } => self.code(&chars, i, depth, &mut out),
+                Mode::BlockComment => {
+                    if c == '*' && next == Some('/') {
+                        self.stack.pop();
+                        mask(&chars[i..i + 2], &mut out);
+                        i + 2
+                    } else {
+                        mask(&chars[i..i + 1], &mut out);
+                        i + 1
+                    }
+                }
+                Mode::Template => self.template(&chars, i, &mut out),
+                Mode::JsxTag { closing } => self.jsx_tag(&chars, i, closing, &mut out),
+                Mode::JsxChildren => self.jsx_children(&chars, i, &mut out),
+            };
+        }
+
+        out
+    }
+
+    fn code(&mut self, chars: &[char], i: usize, depth: usize, out: &mut String) -> usize {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        match c {
+            '/' if next == Some('/') => {
+                mask(&chars[i..], out);
+                return chars.len();
+            }
+            '/' if next == Some('*') => {
+                self.stack.push(Mode::BlockComment);
+                mask(&chars[i..i + 2], out);
+                return i + 2;
+            }
+            '"' | '\'' => {
+                let end = string_end(chars, i);
+                out.push(c);
+                mask(&chars[i + 1..end.saturating_sub(1).max(i + 1)], out);
+                if end > i + 1 && chars[end - 1] == c {
+                    out.push(c);
+                }
+                self.set_prev(c);
+                return end;
+            }
+            '`' => {
+                out.push(c);
+                self.stack.push(Mode::Template);
+                return i + 1;
+            }
+            '/' if self.regex_allowed() => {
+                if let Some(close) = regex_close(chars, i) {
+                    let mut end = close + 1;
+                    while chars.get(end).is_some_and(|c| c.is_ascii_alphabetic()) {
+                        end += 1;
+                    }
+                    out.push('/');
+                    mask(&chars[i + 1..close], out);
+                    out.extend(&chars[close..end]);
+                    self.set_prev('/');
+                    return end;
+                }
+            }
+            '{' => self.set_depth(depth + 1),
+            '}' if depth == 0 && self.stack.len() > 1 => {
+                // Closes a `${` or JSX `{` expression
+                self.stack.pop();
+                out.push(c);
+                self.prev = Some('}');
+                return i + 1;
+            }
+            '}' => self.set_depth(depth.saturating_sub(1)),
+            '<' if self.jsx_allowed() && next.is_some_and(|n| n.is_alphabetic() || n == '>') => {
+                self.stack.push(Mode::JsxTag { closing: false });
+                out.push(c);
+                return i + 1;
+            }
+            _ => {}
+        }
+
+        out.push(c);
+        if !c.is_whitespace() {
+            self.set_prev(c);
+        }
+        i + 1
+    }
+
+    fn template(&mut self, chars: &[char], i: usize, out: &mut String) -> usize {
+        match chars[i] {
+            '\\' => {
+                let end = (i + 2).min(chars.len());
+                mask(&chars[i..end], out);
+                end
+            }
+            '`' => {
+                self.stack.pop();
+                out.push('`');
+                self.set_prev('`');
+                i + 1
+            }
+            '$' if chars.get(i + 1) == Some(&'{') => {
+                out.push_str("${");
+                self.stack.push(Mode::Code { depth: 0 });
+                self.set_prev('{');
+                i + 2
+            }
+            _ => {
+                mask(&chars[i..i + 1], out);
+                i + 1
+            }
+        }
+    }
+
+    fn jsx_tag(&mut self, chars: &[char], i: usize, closing: bool, out: &mut String) -> usize {
+        let c = chars[i];
+        match c {
+            '"' | '\'' => {
+                let end = string_end(chars, i);
+                out.push(c);
+                mask(&chars[i + 1..end.saturating_sub(1).max(i + 1)], out);
+                if end > i + 1 && chars[end - 1] == c {
+                    out.push(c);
+                }
+                return end;
+            }
+            '{' => {
+                self.stack.push(Mode::Code { depth: 0 });
+                self.set_prev('{');
+            }
+            '/' if chars.get(i + 1) == Some(&'>') => {
+                out.push_str("/>");
+                self.close_element();
+                return i + 2;
+            }
+            '>' => {
+                self.stack.pop();
+                if closing {
+                    self.close_element_tag();
+                } else {
+                    self.stack.push(Mode::JsxChildren);
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+        i + 1
+    }
+
+    fn jsx_children(&mut self, chars: &[char], i: usize, out: &mut String) -> usize {
+        match chars[i] {
+            '{' => {
+                self.stack.push(Mode::Code { depth: 0 });
+                self.set_prev('{');
+                out.push('{');
+            }
+            '<' if chars.get(i + 1) == Some(&'/') => {
+                self.stack.pop();
+                self.stack.push(Mode::JsxTag { closing: true });
+                out.push('<');
+            }
+            '<' => {
+                self.stack.push(Mode::JsxTag { closing: false });
+                out.push('<');
+            }
+            _ => mask(&chars[i..i + 1], out),
+        }
+        i + 1
+    }
+
+    /// A self-closing tag ends the whole element
+    fn close_element(&mut self) {
+        self.stack.pop();
+        self.close_element_tag();
+    }
+
+    /// Back in code after an element, `<` is a comparison again
+    fn close_element_tag(&mut self) {
+        if matches!(self.stack.last(), Some(Mode::Code { .. })) {
+            self.prev = Some(')');
+            self.prev_word.clear();
+        }
+    }
+
+    fn set_depth(&mut self, depth: usize) {
+        if let Some(Mode::Code { depth: d }) = self.stack.last_mut() {
+            *d = depth;
+        }
+    }
+
+    fn set_prev(&mut self, c: char) {
+        if is_ident(c) {
+            if !self.prev.is_some_and(is_ident) {
+                self.prev_word.clear();
+            }
+            self.prev_word.push(c);
+        } else {
+            self.prev_word.clear();
+        }
+        self.prev = Some(c);
+    }
+
+    fn after_operator(&self) -> bool {
+        EXPRESSION_KEYWORDS.contains(&self.prev_word.as_str())
+            || match self.prev {
+                None => true,
+                Some(p) => !is_ident(p) && !matches!(p, ')' | ']' | '}' | '"' | '\'' | '`'),
+            }
+    }
+
+    fn regex_allowed(&self) -> bool {
+        self.after_operator()
+    }
+
+    fn jsx_allowed(&self) -> bool {
+        self.jsx && self.after_operator()
+    }
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Replace characters with spaces of the same byte length
+fn mask(chars: &[char], out: &mut String) {
+    for c in chars {
+        for _ in 0..c.len_utf8() {
+            out.push(' ');
+        }
+    }
+}
+
+/// Index after the closing quote, or the line end for unterminated strings
+fn string_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
+
+/// Index of a regex literal's closing `/` on the same line
+fn regex_close(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    let mut in_class = false;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class && i > start + 1 => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mask_all(source: &str, jsx: bool) -> Vec<String> {
+        let mut lexer = JsLexer::new(jsx);
+        source.lines().map(|l| lexer.mask_line(l)).collect()
+    }
+
+    #[test]
+    fn test_strings_comments_and_regex_masked() {
+        let masked = mask_all(
+            "const msg = \"call alert(x)\"; // debugger\n/* console.log(\n  still a comment */ debugger;\nconst re = /console\\.log\\(/g;\nconst ratio = a / b / c;",
+            false,
+        );
+        assert_eq!(masked[0], "const msg = \"             \";            ");
+        assert!(!masked[1].contains("console"));
+        assert_eq!(masked[2].trim(), "debugger;");
+        assert_eq!(masked[3], "const re = /              /g;");
+        assert_eq!(masked[4], "const ratio = a / b / c;");
+    }
+
+    #[test]
+    fn test_template_literals_keep_expressions() {
+        let masked = mask_all(
+            "const s = `debugger ${alert(`nested ${x}`)} done`;\nconst t = `multi\nline alert( ${y}`;",
+            false,
+        );
+        assert_eq!(
+            masked[0],
+            "const s = `         ${alert(`       ${x}`)}     `;"
+        );
+        assert_eq!(masked[2], "            ${y}`;");
+    }
+
+    #[test]
+    fn test_jsx_text_masked() {
+        let source = "return (\n  <div className=\"alert(\" onClick={() => alert(1)}>\n    Don't use debugger here <b>alert(x)</b>\n    {show && <Icon />}\n  </div>\n);\nif (a < b) debugger;";
+        let masked = mask_all(source, true);
+        assert_eq!(
+            masked[1],
+            "  <div className=\"      \" onClick={() => alert(1)}>"
+        );
+        assert_eq!(masked[2].trim(), "<b>        </b>");
+        assert_eq!(masked[3].trim(), "{show && <Icon />}");
+        assert_eq!(masked[6], "if (a < b) debugger;");
+    }
+}
@@ -0,0 +1,203 @@
+use crate::commits;
+use crate::config::PrSizeConfig;
+use crate::{CheckResult, Violation};
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::process::Command;
+
+/// Lines changed in one file, from `git diff --numstat`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl FileChange {
+    fn lines(&self) -> usize {
+        self.added + self.removed
+    }
+}
+
+/// Parse `git diff --numstat -z`; renames carry an empty path followed by
+/// the old and new paths, binary files report `-` counts
+fn parse_numstat(output: &str) -> Vec<FileChange> {
+    let mut fields = output.split('\0');
+    let mut changes = vec![];
+    while let Some(record) = fields.next() {
+        let mut parts = record.splitn(3, '\t');
+        let (Some(added), Some(removed), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let path = if path.is_empty() {
+            let _old = fields.next();
+            fields.next().unwrap_or_default()
+        } else {
+            path
+        };
+        changes.push(FileChange {
+            path: path.to_string(),
+            added: added.parse().unwrap_or(0),
+            removed: removed.parse().unwrap_or(0),
+        });
+    }
+    changes
+}
+
+/// A diff total compared with its budget
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetUsage {
+    /// `None` for the whole diff, otherwise the budget glob
+    pub glob: Option<String>,
+    pub lines: usize,
+    pub files: usize,
+    pub budget: usize,
+}
+
+/// Totals for the whole diff and each budget, plus the number of excluded files
+fn measure(changes: &[FileChange], config: &PrSizeConfig) -> Result<(Vec<BudgetUsage>, usize)> {
+    let exclude = commits::path_set(&config.exclude)?;
+    let counted: Vec<&FileChange> = changes
+        .iter()
+        .filter(|c| !exclude.is_match(&c.path))
+        .collect();
+
+    let mut usage = vec![BudgetUsage {
+        glob: None,
+        lines: counted.iter().map(|c| c.lines()).sum(),
+        files: counted.len(),
+        budget: config.max_lines,
+    }];
+    for (glob, budget) in &config.budgets {
+        let set = commits::path_set(std::slice::from_ref(glob))?;
+        let matched: Vec<&&FileChange> = counted.iter().filter(|c| set.is_match(&c.path)).collect();
+        usage.push(BudgetUsage {
+            glob: Some(glob.clone()),
+            lines: matched.iter().map(|c| c.lines()).sum(),
+            files: matched.len(),
+            budget: *budget,
+        });
+    }
+    Ok((usage, changes.len() - counted.len()))
+}
+
+/// Fail when the changes between the merge base of `base` and `head` exceed
+/// the configured line budgets
+pub fn check_pr_size(base: &str, head: &str, config: &PrSizeConfig) -> Result<CheckResult> {
+    let mut result = CheckResult::new("pr-size");
+
+    let range = format!("{}...{}", base, head);
+    let output = Command::new("git")
+        .args(["diff", "--numstat", "-z", "-M", &range])
+        .output()
+        .context("Failed to run git diff")?;
+    if !output.status.success() {
+        bail!(
+            "git diff failed for {}: {}",
+            range,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let changes = parse_numstat(&String::from_utf8_lossy(&output.stdout));
+    let (usage, excluded) = measure(&changes, config)?;
+
+    let added: usize = changes.iter().map(|c| c.added).sum();
+    let removed: usize = changes.iter().map(|c| c.removed).sum();
+    println!(
+        "📏 {} +{} -{} ({} files, {} excluded)",
+        range,
+        added,
+        removed,
+        changes.len(),
+        excluded
+    );
+
+    for entry in &usage {
+        let name = entry.glob.as_deref().unwrap_or("total");
+        if entry.lines > entry.budget {
+            let message = format!(
+                "{} changes {} lines in {} files (budget {})",
+                name, entry.lines, entry.files, entry.budget
+            );
+            println!("{} {}", "❌".red(), message);
+            result.add_violation(Violation {
+                file: entry.glob.clone().unwrap_or_default(),
+                line: None,
+                message,
+                rule: Some("pr-size".to_string()),
+                ..Default::default()
+            });
+        } else if entry.files > 0 || entry.glob.is_none() {
+            println!("   {}: {} / {} lines", name, entry.lines, entry.budget);
+        }
+    }
+
+    if !result.passed {
+        println!("Split the pull request, e.g. migrations separately from UI changes.");
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_numstat() {
+        let output =
+            "10\t2\tlib/utils.ts\0-\t-\tpublic/logo.png\x003\t1\t\0lib/old.ts\0lib/new.ts\0";
+        assert_eq!(
+            parse_numstat(output),
+            vec![
+                FileChange {
+                    path: "lib/utils.ts".to_string(),
+                    added: 10,
+                    removed: 2
+                },
+                FileChange {
+                    path: "public/logo.png".to_string(),
+                    added: 0,
+                    removed: 0
+                },
+                FileChange {
+                    path: "lib/new.ts".to_string(),
+                    added: 3,
+                    removed: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_budgets_skip_excluded_files() {
+        let change = |path: &str, added| FileChange {
+            path: path.to_string(),
+            added,
+            removed: 0,
+        };
+        let changes = vec![
+            change("supabase/migrations/002_groups.sql", 250),
+            change("components/group-dialog.tsx", 400),
+            change("package-lock.json", 5000),
+            change("public/og.svg", 900),
+        ];
+        let mut config = PrSizeConfig::default();
+        config
+            .budgets
+            .insert("supabase/migrations/**".to_string(), 200);
+        config.budgets.insert("components/**".to_string(), 600);
+
+        let (usage, excluded) = measure(&changes, &config).unwrap();
+        assert_eq!(excluded, 2);
+        assert_eq!((usage[0].lines, usage[0].files), (650, 2));
+        let over: Vec<_> = usage
+            .iter()
+            .filter(|u| u.lines > u.budget)
+            .map(|u| u.glob.as_deref())
+            .collect();
+        assert_eq!(over, vec![Some("supabase/migrations/**")]);
+    }
+}
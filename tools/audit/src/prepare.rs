@@ -0,0 +1,168 @@
+use crate::branch::{self, BranchPolicy};
+use crate::commits;
+use crate::config::{CommitsConfig, Config};
+use crate::CheckResult;
+use anyhow::{Context, Result};
+use std::fs;
+
+/// Inferred scopes beyond this many are left for the author to pick
+const MAX_SCOPES: usize = 2;
+
+/// Scopes whose `[commits.scope_paths]` globs match the staged files
+fn infer_scopes(paths: &[String], config: &CommitsConfig) -> Result<Vec<String>> {
+    let mut scopes = vec![];
+    for (scope, globs) in &config.scope_paths {
+        if !config.scopes.is_empty() && !config.scopes.contains(scope) {
+            continue;
+        }
+        let set = commits::path_set(globs)?;
+        if paths.iter().any(|p| set.is_match(p)) {
+            scopes.push(scope.clone());
+        }
+    }
+    if scopes.len() > MAX_SCOPES {
+        scopes.clear();
+    }
+    Ok(scopes)
+}
+
+/// Header and trailer to pre-fill; None when there is nothing to suggest
+fn template(kind: Option<&str>, scopes: &[String], ticket: Option<&str>) -> Option<String> {
+    let header = match (kind, scopes) {
+        (Some(kind), []) => format!("{}: ", kind),
+        (Some(kind), scopes) => format!("{}({}): ", kind, scopes.join(", ")),
+        (None, _) => String::new(),
+    };
+    if header.is_empty() && ticket.is_none() {
+        return None;
+    }
+    let mut out = format!("{}\n", header);
+    if let Some(ticket) = ticket {
+        out.push_str(&format!("\nRefs: {}\n", ticket));
+    }
+    Some(out)
+}
+
+/// Pre-fill `type(scope): ` and a `Refs:` trailer from the branch and staged paths
+///
+/// Runs as the prepare-commit-msg hook; messages from `-m`, merges, squashes
+/// and amends are left alone.
+pub fn prepare_commit_msg(
+    file: &str,
+    source: Option<&str>,
+    config: &Config,
+) -> Result<CheckResult> {
+    let result = CheckResult::new("prepare-commit-msg");
+    // lefthook passes an empty `{2}` when git gives no source
+    if source.is_some_and(|s| !s.is_empty() && s != "template") {
+        return Ok(result);
+    }
+
+    let existing = fs::read_to_string(file).context("Failed to read commit message file")?;
+    if existing
+        .lines()
+        .any(|l| !l.trim().is_empty() && !l.starts_with('#'))
+    {
+        return Ok(result);
+    }
+
+    let branch = branch::current_branch(None)?;
+    let policy = BranchPolicy::new(&config.branch)?;
+    let name = policy.parse(&branch);
+    let kind = name
+        .as_ref()
+        .and_then(|n| config.commits.branch_types.get(n.kind))
+        .filter(|k| config.commits.types.contains(k));
+    let ticket = name.as_ref().and_then(|n| n.ticket);
+
+    let scopes = match kind {
+        Some(_) => infer_scopes(&commits::staged_files()?, &config.commits)?,
+        None => vec![],
+    };
+
+    if let Some(template) = template(kind.map(String::as_str), &scopes, ticket) {
+        fs::write(file, format!("{}{}", template, existing))
+            .context("Failed to write commit message file")?;
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commits::{lint_message, lint_ticket};
+
+    fn config() -> CommitsConfig {
+        let mut config = CommitsConfig {
+            scopes: ["notifications", "db", "monitors", "audit"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            ..Default::default()
+        };
+        for (scope, glob) in [
+            ("notifications", "lib/notifications.ts"),
+            ("db", "supabase/migrations/**"),
+            ("monitors", "lib/monitor-*.ts"),
+            ("cron", "app/api/cron/**"),
+        ] {
+            config
+                .scope_paths
+                .insert(scope.to_string(), vec![glob.to_string()]);
+        }
+        config
+    }
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_infer_scopes() {
+        let config = config();
+        assert_eq!(
+            infer_scopes(&paths(&["lib/notifications.ts", "README.md"]), &config).unwrap(),
+            vec!["notifications"]
+        );
+        assert_eq!(
+            infer_scopes(
+                &paths(&["supabase/migrations/001_init.sql", "lib/notifications.ts"]),
+                &config
+            )
+            .unwrap(),
+            vec!["db", "notifications"]
+        );
+        // "cron" is not an allowed scope, and three scopes are too many to guess
+        assert!(infer_scopes(&paths(&["app/api/cron/route.ts"]), &config)
+            .unwrap()
+            .is_empty());
+        assert!(infer_scopes(
+            &paths(&[
+                "lib/notifications.ts",
+                "lib/monitor-checker.ts",
+                "supabase/migrations/1.sql"
+            ]),
+            &config
+        )
+        .unwrap()
+        .is_empty());
+    }
+
+    #[test]
+    fn test_template_passes_commit_msg() {
+        let mut config = config();
+        config.rules.insert("ticket-trailer".to_string(), true);
+        let scopes = vec!["db".to_string(), "notifications".to_string()];
+        let prefilled = template(Some("feat"), &scopes, Some("PROJ-12")).unwrap();
+        assert_eq!(prefilled, "feat(db, notifications): \n\nRefs: PROJ-12\n");
+
+        // The author only types the subject
+        let message = prefilled.replacen(": \n", ": add webhook retries\n", 1);
+        assert!(lint_message(&message, &config).is_empty());
+        assert!(lint_ticket(&message, "PROJ-12", &config).is_none());
+
+        assert_eq!(template(Some("fix"), &[], None).unwrap(), "fix: \n");
+        assert!(template(None, &[], None).is_none());
+    }
+}
@@ -0,0 +1,416 @@
+use crate::branch;
+use crate::changelog::{self, Entry};
+use crate::commits;
+use crate::config::ReleaseConfig;
+use crate::json;
+use crate::{CheckResult, Violation};
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Version fields rewritten in JSON manifests; package-lock.json repeats the
+/// root package under `packages[""]`
+const JSON_VERSION_POINTERS: &[&str] = &["/version", "/packages//version"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        })
+    }
+}
+
+/// A `major.minor.patch` release version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// Parse `1.2.3`; pre-release and build suffixes are not supported
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.trim().split('.').map(|p| p.parse::<u64>().ok());
+        let version = Self {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+        };
+        parts.next().is_none().then_some(version)
+    }
+
+    pub fn bump(self, bump: Bump) -> Self {
+        match bump {
+            Bump::Major => Self {
+                major: self.major + 1,
+                minor: 0,
+                patch: 0,
+            },
+            Bump::Minor => Self {
+                minor: self.minor + 1,
+                patch: 0,
+                ..self
+            },
+            Bump::Patch => Self {
+                patch: self.patch + 1,
+                ..self
+            },
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Breaking changes are major, features minor, anything else a patch
+pub fn bump_for(entries: &[Entry]) -> Option<Bump> {
+    entries
+        .iter()
+        .map(|e| {
+            if e.breaking.is_some() {
+                Bump::Major
+            } else if e.kind == "feat" {
+                Bump::Minor
+            } else {
+                Bump::Patch
+            }
+        })
+        .max()
+}
+
+/// Rewrite the version of a package.json or package-lock.json, keeping formatting
+fn set_json_version(text: &str, version: &Version) -> Result<String> {
+    let root = json::parse(text)?;
+    let mut spans: Vec<(usize, usize)> = JSON_VERSION_POINTERS
+        .iter()
+        .filter_map(|p| root.pointer(p))
+        .filter(|node| node.as_str().is_some())
+        .map(|node| (node.start, node.end))
+        .collect();
+    if spans.is_empty() {
+        bail!("No \"version\" field");
+    }
+
+    spans.sort_unstable();
+    let mut out = text.to_string();
+    for (start, end) in spans.into_iter().rev() {
+        out.replace_range(start..end, &format!("\"{}\"", version));
+    }
+    Ok(out)
+}
+
+/// Rewrite `version` in the `[package]` table of a Cargo.toml
+fn set_cargo_version(text: &str, version: &Version) -> Result<String> {
+    let mut in_package = false;
+    let mut replaced = false;
+    let mut out: Vec<String> = vec![];
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_package = trimmed == "[package]";
+        } else if in_package && !replaced {
+            let key = trimmed.split('=').next().unwrap_or_default().trim();
+            if key == "version" && trimmed.contains('"') {
+                out.push(format!("version = \"{}\"", version));
+                replaced = true;
+                continue;
+            }
+        }
+        out.push(line.to_string());
+    }
+
+    if !replaced {
+        bail!("No version in [package]");
+    }
+    let mut out = out.join("\n");
+    if text.ends_with('\n') {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// `name` in the `[package]` table of a Cargo.toml
+fn cargo_package_name(text: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_package = trimmed == "[package]";
+        } else if in_package {
+            if let Some((key, value)) = trimmed.split_once('=') {
+                if key.trim() == "name" {
+                    return Some(value.trim().trim_matches('"').to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Rewrite the version of package `name` in a Cargo.lock
+fn set_lock_version(text: &str, name: &str, version: &Version) -> Result<String> {
+    let package_name = format!("name = \"{}\"", name);
+    let mut in_package = false;
+    let mut replaced = false;
+    let mut out: Vec<String> = vec![];
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_package = false;
+        } else if trimmed == package_name {
+            in_package = true;
+        } else if in_package && !replaced && trimmed.starts_with("version = ") {
+            out.push(format!("version = \"{}\"", version));
+            replaced = true;
+            continue;
+        }
+        out.push(line.to_string());
+    }
+
+    if !replaced {
+        bail!("No package '{}'", name);
+    }
+    let mut out = out.join("\n");
+    if text.ends_with('\n') {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Keep the Cargo.lock next to a bumped Cargo.toml in sync, returning its path
+///
+/// Otherwise the next build rewrites it and `--locked` builds fail.
+fn update_cargo_lock(manifest: &str, text: &str, version: &Version) -> Result<Option<String>> {
+    if !manifest.ends_with("Cargo.toml") {
+        return Ok(None);
+    }
+    let lock = Path::new(manifest).with_file_name("Cargo.lock");
+    let Some(name) = cargo_package_name(text).filter(|_| lock.exists()) else {
+        return Ok(None);
+    };
+    let lock = lock.to_string_lossy().into_owned();
+    let lock_text =
+        fs::read_to_string(&lock).with_context(|| format!("Failed to read {}", lock))?;
+    let updated = set_lock_version(&lock_text, &name, version)
+        .with_context(|| format!("Failed to update version in {}", lock))?;
+    fs::write(&lock, updated).with_context(|| format!("Failed to write {}", lock))?;
+    Ok(Some(lock))
+}
+
+fn set_version(path: &str, text: &str, version: &Version) -> Result<String> {
+    if path.ends_with(".json") {
+        set_json_version(text, version)
+    } else if path.ends_with(".toml") {
+        set_cargo_version(text, version)
+    } else {
+        bail!("Unsupported manifest type")
+    }
+    .with_context(|| format!("Failed to update version in {}", path))
+}
+
+/// Version recorded in a JSON manifest, used when no release tag exists yet
+fn manifest_version(path: &str) -> Result<Version> {
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+    let root = json::parse(&text).with_context(|| format!("Invalid JSON in {}", path))?;
+    root.get("version")
+        .and_then(|v| v.as_str())
+        .and_then(Version::parse)
+        .with_context(|| format!("No semver \"version\" in {}", path))
+}
+
+fn git(args: &[&str]) -> Result<()> {
+    let status = Command::new("git")
+        .args(args)
+        .status()
+        .context("Failed to run git")?;
+    if !status.success() {
+        bail!("git {} failed", args.join(" "));
+    }
+    Ok(())
+}
+
+/// Bump manifest versions from the conventional commits since the last tag
+pub fn release(tag: bool, dry_run: bool, config: &ReleaseConfig) -> Result<CheckResult> {
+    let mut result = CheckResult::new("release");
+
+    let branch = branch::current_branch(None)?;
+    if !config.branches.contains(&branch) {
+        let message = format!(
+            "Releases must be cut from {} (on {})",
+            config.branches.join(", "),
+            branch
+        );
+        println!("{} {}", "❌".red(), message);
+        result.add_violation(Violation {
+            file: String::new(),
+            line: None,
+            message,
+            rule: Some("release-branch".to_string()),
+            ..Default::default()
+        });
+        return Ok(result);
+    }
+
+    let pattern = format!("{}[0-9]*", config.tag_prefix);
+    let last_tag = changelog::latest_tag("HEAD", Some(&pattern));
+    let current = match &last_tag {
+        Some(tag) => tag
+            .strip_prefix(&config.tag_prefix)
+            .and_then(Version::parse)
+            .with_context(|| format!("Latest tag {} is not a semver version", tag))?,
+        None => {
+            let manifest = config
+                .manifests
+                .iter()
+                .find(|m| m.ends_with(".json"))
+                .context("No tag and no JSON manifest to read the current version from")?;
+            manifest_version(manifest)?
+        }
+    };
+
+    let range = match &last_tag {
+        Some(tag) => format!("{}..HEAD", tag),
+        None => "HEAD".to_string(),
+    };
+    let entries: Vec<Entry> = commits::log_commits(&[&range], true)?
+        .iter()
+        .filter_map(changelog::parse_entry)
+        .collect();
+    let Some(bump) = bump_for(&entries) else {
+        println!(
+            "ℹ️  No conventional commits since {}",
+            last_tag.as_deref().unwrap_or("the first commit")
+        );
+        return Ok(result);
+    };
+
+    let next = current.bump(bump);
+    let tag_name = format!("{}{}", config.tag_prefix, next);
+    println!(
+        "📦 {} → {} ({} release, {} commits in {})",
+        current,
+        next.to_string().green(),
+        bump,
+        entries.len(),
+        range
+    );
+    if dry_run {
+        return Ok(result);
+    }
+
+    let mut updated = vec![];
+    for manifest in &config.manifests {
+        let text =
+            fs::read_to_string(manifest).with_context(|| format!("Failed to read {}", manifest))?;
+        fs::write(manifest, set_version(manifest, &text, &next)?)
+            .with_context(|| format!("Failed to write {}", manifest))?;
+        println!("   Updated {}", manifest);
+        updated.push(manifest.clone());
+        if let Some(lock) = update_cargo_lock(manifest, &text, &next)? {
+            println!("   Updated {}", lock);
+            updated.push(lock);
+        }
+    }
+
+    if tag {
+        let message = format!("chore: release {}", tag_name);
+        let mut args = vec!["commit", "-m", message.as_str(), "--"];
+        args.extend(updated.iter().map(String::as_str));
+        git(&args)?;
+        git(&[
+            "tag",
+            "-a",
+            &tag_name,
+            "-m",
+            &format!("Release {}", tag_name),
+        ])?;
+        println!("🏷️  Tagged {}", tag_name);
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: &str, breaking: bool) -> Entry {
+        Entry {
+            kind: kind.to_string(),
+            scope: None,
+            subject: "change".to_string(),
+            sha: "abc".to_string(),
+            references: vec![],
+            breaking: breaking.then(|| "change".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_bump_from_commits() {
+        let v = Version::parse("0.1.9").unwrap();
+        assert_eq!(bump_for(&[]), None);
+        assert_eq!(
+            bump_for(&[entry("fix", false), entry("chore", false)]),
+            Some(Bump::Patch)
+        );
+        assert_eq!(
+            bump_for(&[entry("fix", false), entry("feat", false)]),
+            Some(Bump::Minor)
+        );
+        assert_eq!(
+            bump_for(&[entry("feat", false), entry("fix", true)]),
+            Some(Bump::Major)
+        );
+        assert_eq!(v.bump(Bump::Patch).to_string(), "0.1.10");
+        assert_eq!(v.bump(Bump::Minor).to_string(), "0.2.0");
+        assert_eq!(v.bump(Bump::Major).to_string(), "1.0.0");
+        assert!(Version::parse("1.0.0-beta.1").is_none());
+    }
+
+    #[test]
+    fn test_set_manifest_versions() {
+        let next = Version::parse("0.2.0").unwrap();
+        let lock = "{\n  \"name\": \"uptime-monitor\",\n  \"version\": \"0.1.0\",\n  \"packages\": {\n    \"\": {\n      \"version\": \"0.1.0\"\n    },\n    \"node_modules/x\": {\n      \"version\": \"0.1.0\"\n    }\n  }\n}\n";
+        assert_eq!(
+            set_json_version(lock, &next).unwrap(),
+            lock.replacen("0.1.0", "0.2.0", 2)
+        );
+
+        let cargo = "[package]\nname = \"audit\"\nversion = \"0.1.0\"\n\n[dependencies]\nclap = { version = \"4.5\" }\nversion = \"1\"\n";
+        assert_eq!(
+            set_cargo_version(cargo, &next).unwrap(),
+            cargo.replacen("0.1.0", "0.2.0", 1)
+        );
+        assert!(set_cargo_version("[dependencies]\nversion = \"1\"\n", &next).is_err());
+
+        assert_eq!(cargo_package_name(cargo).as_deref(), Some("audit"));
+        let lock = "version = 4\n\n[[package]]\nname = \"anyhow\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"audit\"\nversion = \"0.1.0\"\ndependencies = [\n \"anyhow\",\n]\n";
+        assert_eq!(
+            set_lock_version(lock, "audit", &next).unwrap(),
+            lock.replacen(
+                "name = \"audit\"\nversion = \"0.1.0\"",
+                "name = \"audit\"\nversion = \"0.2.0\"",
+                1
+            )
+        );
+        assert!(set_lock_version(lock, "missing", &next).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{CheckResult, Severity, Violation};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Render a check result as a SARIF 2.1.0 log for code scanning uploads
+pub fn to_sarif(result: &CheckResult) -> Value {
+    let rule_ids: BTreeSet<String> = result
+        .violations
+        .iter()
+        .map(|v| rule_id(result, v))
+        .collect();
+
+    let rules: Vec<Value> = rule_ids
+        .iter()
+        .map(|id| json!({ "id": id, "name": id }))
+        .collect();
+
+    let results: Vec<Value> = result
+        .violations
+        .iter()
+        .map(|v| {
+            let mut entry = json!({
+                "ruleId": rule_id(result, v),
+                "level": level(v.severity),
+                "message": { "text": v.message },
+            });
+            if !v.file.is_empty() {
+                entry["locations"] = json!([{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": v.file },
+                        "region": { "startLine": v.line.unwrap_or(1) },
+                    }
+                }]);
+            }
+            if let Some(commit) = &v.commit {
+                entry["properties"] = json!({
+                    "commit": commit,
+                    "author": v.author,
+                    "date": v.date,
+                });
+            }
+            entry
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "audit",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn rule_id(result: &CheckResult, violation: &Violation) -> String {
+    violation
+        .rule
+        .clone()
+        .unwrap_or_else(|| result.check.clone())
+}
+
+fn level(severity: Option<Severity>) -> &'static str {
+    match severity {
+        Some(Severity::Info) => "note",
+        Some(Severity::Low | Severity::Medium) => "warning",
+        Some(Severity::High | Severity::Critical) | None => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sarif_levels_and_locations() {
+        let mut result = CheckResult::new("secrets-check");
+        result.add_violation(Violation {
+            file: "lib/a.ts".to_string(),
+            line: Some(3),
+            message: "Potential GitHub personal access token".to_string(),
+            rule: Some("github-pat".to_string()),
+            severity: Some(Severity::Critical),
+            ..Default::default()
+        });
+        result.add_violation(Violation {
+            message: "Invalid branch name".to_string(),
+            ..Default::default()
+        });
+
+        let sarif = to_sarif(&result);
+        let results = &sarif["runs"][0]["results"];
+        assert_eq!(results[0]["ruleId"], "github-pat");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            3
+        );
+        assert_eq!(results[1]["ruleId"], "secrets-check");
+        assert!(results[1].get("locations").is_none());
+    }
+}
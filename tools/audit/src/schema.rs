@@ -0,0 +1,574 @@
+use crate::commits;
+use crate::config::JsonConfig;
+use crate::json::{Node, Value};
+use anyhow::{Context, Result};
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+use serde_json::Value as Schema;
+use std::fs;
+
+/// Schemas shipped with the binary: name, `$schema` URLs that select it, source
+///
+/// These are trimmed copies of the upstream schemas covering the keys this
+/// repository uses, so validation works offline and in hooks. Only objects
+/// whose keys are listed in full (e.g. Vercel header rules) set
+/// `additionalProperties: false`; elsewhere unknown keys are allowed.
+const BUNDLED: &[(&str, &[&str], &str)] = &[
+    (
+        "vercel",
+        &["https://openapi.vercel.sh/vercel.json"],
+        include_str!("../schemas/vercel.schema.json"),
+    ),
+    (
+        "components",
+        &["https://ui.shadcn.com/schema.json"],
+        include_str!("../schemas/components.schema.json"),
+    ),
+    (
+        "webmanifest",
+        &[
+            "https://json.schemastore.org/web-manifest-combined.json",
+            "https://json.schemastore.org/web-manifest.json",
+        ],
+        include_str!("../schemas/webmanifest.schema.json"),
+    ),
+    (
+        "tsconfig",
+        &[
+            "https://json.schemastore.org/tsconfig",
+            "https://json.schemastore.org/tsconfig.json",
+        ],
+        include_str!("../schemas/tsconfig.schema.json"),
+    ),
+    (
+        "package",
+        &[
+            "https://json.schemastore.org/package",
+            "https://json.schemastore.org/package.json",
+        ],
+        include_str!("../schemas/package.schema.json"),
+    ),
+];
+
+/// Nesting limit for `$ref` chains, which may be recursive
+const MAX_DEPTH: usize = 64;
+
+/// A schema violation at a JSON pointer into the validated document
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    pub pointer: String,
+    pub message: String,
+    /// Byte offset of the offending value
+    pub start: usize,
+}
+
+/// `[json.schemas]` globs resolved to bundled or on-disk schemas
+pub struct Schemas {
+    globs: Vec<(GlobMatcher, String)>,
+}
+
+impl Schemas {
+    pub fn new(config: &JsonConfig) -> Result<Self> {
+        let globs = config
+            .schemas
+            .iter()
+            .map(|(glob, schema)| {
+                let matcher = Glob::new(glob)
+                    .with_context(|| format!("Invalid glob '{}'", glob))?
+                    .compile_matcher();
+                Ok((matcher, schema.clone()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { globs })
+    }
+
+    /// Whether a configured glob covers `path`, e.g. a `.webmanifest` file
+    pub fn applies(&self, path: &str) -> bool {
+        self.globs.iter().any(|(glob, _)| glob.is_match(path))
+    }
+
+    /// Schema for a document: the first matching glob, else its `$schema` URL
+    ///
+    /// Returns the schema's display name with it; None when neither applies.
+    pub fn lookup(&self, path: &str, root: &Node) -> Result<Option<(String, Schema)>> {
+        if let Some((_, name)) = self.globs.iter().find(|(glob, _)| glob.is_match(path)) {
+            return load(name).map(|schema| Some((name.clone(), schema)));
+        }
+        let url = root.get("$schema").and_then(Node::as_str);
+        match url.and_then(|url| BUNDLED.iter().find(|(_, urls, _)| urls.contains(&url))) {
+            Some((name, _, _)) => load(name).map(|schema| Some((name.to_string(), schema))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A bundled schema by name, or a schema file relative to the repository root
+fn load(name: &str) -> Result<Schema> {
+    let source = match BUNDLED.iter().find(|(n, _, _)| *n == name) {
+        Some((_, _, source)) => source.to_string(),
+        None => {
+            fs::read_to_string(name).with_context(|| format!("Failed to read schema '{}'", name))?
+        }
+    };
+    serde_json::from_str(&source).with_context(|| format!("Invalid schema '{}'", name))
+}
+
+/// Validate a document against a JSON Schema (draft-07 subset)
+///
+/// Supports `$ref` within the schema, `type`, `enum`, `const`, string, number
+/// and array bounds, `pattern`, object `properties`, `required`,
+/// `patternProperties`, `additionalProperties` and the `allOf`/`anyOf`/
+/// `oneOf`/`not` combinators. Other keywords are ignored.
+pub fn validate(schema: &Schema, document: &Node) -> Vec<SchemaError> {
+    let mut errors = vec![];
+    Validator { root: schema }.check(schema, document, "", 0, &mut errors);
+    errors
+}
+
+struct Validator<'a> {
+    root: &'a Schema,
+}
+
+impl Validator<'_> {
+    fn check(
+        &self,
+        schema: &Schema,
+        node: &Node,
+        pointer: &str,
+        depth: usize,
+        errors: &mut Vec<SchemaError>,
+    ) {
+        let schema = match schema {
+            Schema::Bool(true) => return,
+            Schema::Bool(false) => {
+                let message = "Value is not allowed here".to_string();
+                return self.push(errors, pointer, node, message);
+            }
+            Schema::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Schema::as_str) {
+            let target = reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer));
+            match target {
+                Some(_) if depth >= MAX_DEPTH => {
+                    let message = format!("Too deeply nested $ref '{}'", reference);
+                    return self.push(errors, pointer, node, message);
+                }
+                Some(target) => self.check(target, node, pointer, depth + 1, errors),
+                None => {
+                    let message = format!("Unresolvable $ref '{}'", reference);
+                    return self.push(errors, pointer, node, message);
+                }
+            }
+        }
+
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Schema::String(t) => vec![t.as_str()],
+                Schema::Array(ts) => ts.iter().filter_map(Schema::as_str).collect(),
+                _ => vec![],
+            };
+            if !types.iter().any(|t| has_type(node, t)) {
+                return self.push(
+                    errors,
+                    pointer,
+                    node,
+                    format!("Expected {}, found {}", types.join(" or "), type_name(node)),
+                );
+            }
+        }
+        if let Some(Schema::Array(allowed)) = schema.get("enum") {
+            if !allowed.iter().any(|v| equals(node, v)) {
+                let allowed: Vec<String> = allowed.iter().map(Schema::to_string).collect();
+                self.push(
+                    errors,
+                    pointer,
+                    node,
+                    format!("Expected one of {}", allowed.join(", ")),
+                );
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if !equals(node, expected) {
+                self.push(errors, pointer, node, format!("Expected {}", expected));
+            }
+        }
+
+        match &node.value {
+            Value::String(s) => self.check_string(schema, s, node, pointer, errors),
+            Value::Number(n) => self.check_number(schema, *n, node, pointer, errors),
+            Value::Array(items) => self.check_array(schema, items, node, pointer, depth, errors),
+            Value::Object(members) => {
+                self.check_object(schema, members, node, pointer, depth, errors)
+            }
+            Value::Null | Value::Bool(_) => {}
+        }
+
+        self.check_combinators(schema, node, pointer, depth, errors);
+    }
+
+    fn push(&self, errors: &mut Vec<SchemaError>, pointer: &str, node: &Node, message: String) {
+        errors.push(SchemaError {
+            pointer: pointer.to_string(),
+            message,
+            start: node.start,
+        });
+    }
+
+    fn check_string(
+        &self,
+        schema: &serde_json::Map<String, Schema>,
+        s: &str,
+        node: &Node,
+        pointer: &str,
+        errors: &mut Vec<SchemaError>,
+    ) {
+        let length = s.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Schema::as_u64) {
+            if length < min {
+                let message = format!("Must be at least {} characters long", min);
+                self.push(errors, pointer, node, message);
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Schema::as_u64) {
+            if length > max {
+                let message = format!("Must be at most {} characters long", max);
+                self.push(errors, pointer, node, message);
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Schema::as_str) {
+            // Patterns the regex crate cannot compile (lookarounds) are skipped
+            if Regex::new(pattern).is_ok_and(|re| !re.is_match(s)) {
+                let message = format!("\"{}\" does not match pattern '{}'", s, pattern);
+                self.push(errors, pointer, node, message);
+            }
+        }
+    }
+
+    fn check_number(
+        &self,
+        schema: &serde_json::Map<String, Schema>,
+        n: f64,
+        node: &Node,
+        pointer: &str,
+        errors: &mut Vec<SchemaError>,
+    ) {
+        if let Some(min) = schema.get("minimum").and_then(Schema::as_f64) {
+            if n < min {
+                self.push(errors, pointer, node, format!("Must be at least {}", min));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(Schema::as_f64) {
+            if n > max {
+                self.push(errors, pointer, node, format!("Must be at most {}", max));
+            }
+        }
+    }
+
+    fn check_array(
+        &self,
+        schema: &serde_json::Map<String, Schema>,
+        items: &[Node],
+        node: &Node,
+        pointer: &str,
+        depth: usize,
+        errors: &mut Vec<SchemaError>,
+    ) {
+        let count = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(Schema::as_u64) {
+            if count < min {
+                self.push(
+                    errors,
+                    pointer,
+                    node,
+                    format!("Must have at least {} items", min),
+                );
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Schema::as_u64) {
+            if count > max {
+                self.push(
+                    errors,
+                    pointer,
+                    node,
+                    format!("Must have at most {} items", max),
+                );
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                let item_pointer = format!("{}/{}", pointer, i);
+                self.check(item_schema, item, &item_pointer, depth, errors);
+            }
+        }
+    }
+
+    fn check_object(
+        &self,
+        schema: &serde_json::Map<String, Schema>,
+        members: &[(String, Node)],
+        node: &Node,
+        pointer: &str,
+        depth: usize,
+        errors: &mut Vec<SchemaError>,
+    ) {
+        let properties = schema.get("properties").and_then(Schema::as_object);
+        let patterns: Vec<(Regex, &Schema)> = schema
+            .get("patternProperties")
+            .and_then(Schema::as_object)
+            .into_iter()
+            .flatten()
+            .filter_map(|(pattern, s)| Some((Regex::new(pattern).ok()?, s)))
+            .collect();
+
+        if let Some(Schema::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Schema::as_str) {
+                if !members.iter().any(|(k, _)| k == key) {
+                    let message = format!("Missing required property '{}'", key);
+                    self.push(errors, pointer, node, message);
+                }
+            }
+        }
+
+        let known: Vec<String> = properties
+            .map(|p| p.keys().cloned().collect())
+            .unwrap_or_default();
+        for (key, value) in members {
+            let member_pointer = format!("{}/{}", pointer, escape(key));
+            let mut matched = false;
+            if let Some(property) = properties.and_then(|p| p.get(key)) {
+                matched = true;
+                self.check(property, value, &member_pointer, depth, errors);
+            }
+            for (_, property) in patterns.iter().filter(|(re, _)| re.is_match(key)) {
+                matched = true;
+                self.check(property, value, &member_pointer, depth, errors);
+            }
+            if matched {
+                continue;
+            }
+            match schema.get("additionalProperties") {
+                Some(Schema::Bool(false)) => {
+                    let message = match commits::suggest(key, &known) {
+                        Some(close) => {
+                            format!("Unknown property '{}', did you mean '{}'?", key, close)
+                        }
+                        None => format!("Unknown property '{}'", key),
+                    };
+                    self.push(errors, &member_pointer, value, message);
+                }
+                Some(additional) => self.check(additional, value, &member_pointer, depth, errors),
+                None => {}
+            }
+        }
+    }
+
+    fn check_combinators(
+        &self,
+        schema: &serde_json::Map<String, Schema>,
+        node: &Node,
+        pointer: &str,
+        depth: usize,
+        errors: &mut Vec<SchemaError>,
+    ) {
+        if let Some(Schema::Array(all)) = schema.get("allOf") {
+            for branch in all {
+                self.check(branch, node, pointer, depth, errors);
+            }
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            let Some(Schema::Array(branches)) = schema.get(keyword) else {
+                continue;
+            };
+            let results: Vec<Vec<SchemaError>> = branches
+                .iter()
+                .map(|branch| {
+                    let mut branch_errors = vec![];
+                    self.check(branch, node, pointer, depth, &mut branch_errors);
+                    branch_errors
+                })
+                .collect();
+            let passing = results.iter().filter(|r| r.is_empty()).count();
+            if passing == 0 {
+                // The closest branch explains the failure best
+                if let Some(closest) = results.into_iter().min_by_key(Vec::len) {
+                    errors.extend(closest);
+                }
+            } else if keyword == "oneOf" && passing > 1 {
+                let message = "Matches more than one schema in oneOf".to_string();
+                self.push(errors, pointer, node, message);
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            let mut not_errors = vec![];
+            self.check(not, node, pointer, depth, &mut not_errors);
+            if not_errors.is_empty() {
+                let message = "Matches a schema it must not match".to_string();
+                self.push(errors, pointer, node, message);
+            }
+        }
+    }
+}
+
+/// Escape a key for use as a JSON pointer token (RFC 6901)
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn type_name(node: &Node) -> &'static str {
+    match &node.value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.fract() == 0.0 => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(node: &Node, kind: &str) -> bool {
+    match (kind, type_name(node)) {
+        ("number", "integer") => true,
+        (kind, actual) => kind == actual,
+    }
+}
+
+/// Structural equality between a document value and a schema literal
+fn equals(node: &Node, value: &Schema) -> bool {
+    match (&node.value, value) {
+        (Value::Null, Schema::Null) => true,
+        (Value::Bool(a), Schema::Bool(b)) => a == b,
+        (Value::Number(a), Schema::Number(b)) => b.as_f64() == Some(*a),
+        (Value::String(a), Schema::String(b)) => a == b,
+        (Value::Array(a), Schema::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(n, v)| equals(n, v))
+        }
+        (Value::Object(a), Schema::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, n)| b.get(k).is_some_and(|v| equals(n, v)))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{self, line_col};
+
+    fn errors(name: &str, text: &str) -> Vec<(String, String, usize)> {
+        let schema = load(name).unwrap();
+        validate(&schema, &json::parse(text).unwrap())
+            .into_iter()
+            .map(|e| (e.pointer, e.message, line_col(text, e.start).0))
+            .collect()
+    }
+
+    #[test]
+    fn test_vercel_header_typo_reported_with_pointer_and_line() {
+        let text = r#"{
+  "$schema": "https://openapi.vercel.sh/vercel.json",
+  "headers": [
+    {
+      "source": "/api/(.*)",
+      "headers": [{ "key": "Cache-Control", "vaule": "no-store" }]
+    }
+  ],
+  "crons": [{ "path": "/api/cron", "schedule": "*/5 * * * *" }],
+  "cleanUrls": "yes"
+}"#;
+        assert_eq!(
+            errors("vercel", text),
+            vec![
+                (
+                    "/headers/0/headers/0".to_string(),
+                    "Missing required property 'value'".to_string(),
+                    6
+                ),
+                (
+                    "/headers/0/headers/0/vaule".to_string(),
+                    "Unknown property 'vaule', did you mean 'value'?".to_string(),
+                    6
+                ),
+                (
+                    "/cleanUrls".to_string(),
+                    "Expected boolean, found string".to_string(),
+                    10
+                ),
+            ]
+        );
+
+        // Top-level keys outside the trimmed subset stay valid
+        let legacy = r#"{"builds": [{"src": "app.js", "use": "@vercel/node"}], "relatedProjects": ["prj_123"]}"#;
+        assert_eq!(errors("vercel", legacy), vec![]);
+
+        // Conditions go through a $ref and anyOf
+        let conditions = r#"{"redirects": [{"source": "/a", "destination": "/b",
+            "has": [{"type": "header", "kye": "x-debug"}], "statusCode": 304}]}"#;
+        let found = errors("vercel", conditions);
+        let pointers: Vec<&str> = found.iter().map(|(p, _, _)| p.as_str()).collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "/redirects/0/has/0",
+                "/redirects/0/has/0/kye",
+                "/redirects/0/statusCode"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bundled_schemas_accept_repo_files() {
+        for (name, file) in [
+            ("vercel", "vercel.json"),
+            ("components", "components.json"),
+            ("webmanifest", "public/site.webmanifest"),
+            ("tsconfig", "tsconfig.json"),
+            ("package", "package.json"),
+        ] {
+            let path = format!("{}/../../{}", env!("CARGO_MANIFEST_DIR"), file);
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+            assert_eq!(errors(name, &text), vec![], "{}", file);
+        }
+
+        let found = errors(
+            "tsconfig",
+            r#"{"compilerOptions": {"target": "es2099", "strict": "true"}}"#,
+        );
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].0, "/compilerOptions/target");
+        assert_eq!(
+            errors("package", r#"{"name": "Uptime Monitor"}"#)[0].0,
+            "/name"
+        );
+    }
+
+    #[test]
+    fn test_lookup_by_glob_then_schema_url() {
+        let schemas = Schemas::new(&JsonConfig::default()).unwrap();
+        assert!(schemas.applies("public/site.webmanifest"));
+        assert!(!schemas.applies("supabase-mcp.json"));
+
+        let root = json::parse(r#"{"$schema": "https://ui.shadcn.com/schema.json"}"#).unwrap();
+        let (name, _) = schemas
+            .lookup("ui/components.json", &root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(name, "components");
+        let (name, _) = schemas
+            .lookup("apps/web/package.json", &root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(name, "package");
+
+        let bare = json::parse("{}").unwrap();
+        assert!(schemas.lookup("data.json", &bare).unwrap().is_none());
+    }
+}
@@ -0,0 +1,777 @@
+use crate::config::{DecodeConfig, SecretsConfig};
+use crate::decode;
+use crate::detectors::{self, CompiledDetector};
+use crate::engine::RuleSet;
+use crate::{has_ignore_marker, Severity};
+use crate::{jwt, redact};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, BufRead};
+use std::ops::Range;
+use std::path::Path;
+
+/// Key names whose values are treated as credentials in structured files
+const SENSITIVE_KEY_PATTERN: &str = r"(?i)(service[_-]?role|secret|passw(or)?d|token|api[_-]?key|private[_-]?key|signing[_-]?key|access[_-]?key|credential|webhook[_-]?url)";
+
+/// Values that are obviously documentation rather than real credentials
+const PLACEHOLDER_PATTERN: &str = r"(?i)(^your[_-]|^<.*>$|^\$\{.*\}$|^\$[A-Z_]+$|\$\{\{|x{6,}|changeme|example|placeholder|^generate_|^replace|^dummy|^\*+$|\.\.\.|process\.env)";
+
+/// A fragment of a string literal split with `+` (e.g. `"abc" +\n "def"`)
+const CONCAT_PATTERN: &str = r#"(['"`])\s*\+\s*(['"`])"#;
+
+/// Minimum length of a base64 line considered as PEM key material
+const MIN_PEM_BODY_LEN: usize = 40;
+
+/// A potential secret found while scanning a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub line: usize,
+    pub message: String,
+    pub pattern: Option<String>,
+    pub rule: String,
+    pub provider: String,
+    pub severity: Severity,
+    /// Redacted preview of the secret value, when it can be isolated
+    pub preview: Option<String>,
+    /// Hash of the secret value, to recognise it again without keeping it
+    pub fingerprint: Option<u64>,
+}
+
+impl Finding {
+    fn from_detector(line: usize, message: String, detector: &CompiledDetector) -> Self {
+        Self {
+            line,
+            message,
+            pattern: Some(detector.pattern.clone()),
+            rule: detector.id.clone(),
+            provider: detector.provider.clone(),
+            severity: Severity::High,
+            preview: None,
+            fingerprint: None,
+        }
+    }
+}
+
+/// Secret scanner combining per-line, multi-line and structure-aware passes
+pub struct SecretScanner {
+    detectors: Vec<CompiledDetector>,
+    rules: RuleSet,
+    sensitive_key: Regex,
+    placeholder: Regex,
+    concat: Regex,
+}
+
+impl SecretScanner {
+    pub fn new(config: &SecretsConfig) -> Self {
+        let (detectors, rules) = detectors::compile(&config.extra_patterns);
+        Self {
+            detectors,
+            rules,
+            sensitive_key: Regex::new(SENSITIVE_KEY_PATTERN).unwrap(),
+            placeholder: Regex::new(PLACEHOLDER_PATTERN).unwrap(),
+            concat: Regex::new(CONCAT_PATTERN).unwrap(),
+        }
+    }
+
+    /// Number of compiled detectors
+    pub fn detector_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Scan file content, returning at most one finding per line
+    pub fn scan(&self, path: &str, content: &str) -> Vec<Finding> {
+        self.scan_reader(path, content.as_bytes())
+            .unwrap_or_default()
+    }
+
+    /// Stream a file line by line; only structured files are kept in memory
+    ///
+    /// Fails with `InvalidData` when the content is not UTF-8.
+    pub fn scan_reader<R: BufRead>(&self, path: &str, mut reader: R) -> io::Result<Vec<Finding>> {
+        let structured = StructuredKind::from_path(path);
+        let mut content = String::new();
+        let mut state = ScanState::default();
+        let mut buf = vec![];
+        let mut line_no = 0;
+
+        while reader.read_until(b'\n', &mut buf)? > 0 {
+            line_no += 1;
+            let line = std::str::from_utf8(&buf)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if structured.is_some() {
+                content.push_str(line);
+            }
+            self.push_line(&mut state, line_no, line.trim_end_matches(['\n', '\r']));
+            buf.clear();
+        }
+        self.flush_span(&mut state);
+
+        if let Some(kind) = structured {
+            for finding in self.scan_structured(kind, &content) {
+                state.add(finding);
+            }
+        }
+
+        Ok(state.findings.into_values().collect())
+    }
+
+    /// Scan archive entries and non-UTF-8 content, keyed by display path
+    ///
+    /// Content a decode limit kept from being scanned is reported as an Info
+    /// finding, so a truncated archive is never mistaken for a clean one.
+    pub fn scan_decoded(
+        &self,
+        path: &str,
+        bytes: &[u8],
+        limits: &DecodeConfig,
+    ) -> Vec<(String, Finding)> {
+        let (decoded, truncated) = decode::decode(path, bytes, limits);
+        let mut findings: Vec<(String, Finding)> = decoded
+            .into_iter()
+            .flat_map(|d| {
+                self.scan(&d.path, &d.text)
+                    .into_iter()
+                    .map(move |f| (d.path.clone(), f))
+            })
+            .collect();
+        findings.extend(truncated.into_iter().map(|t| {
+            let finding = Finding {
+                line: 1,
+                message: format!(
+                    "Not fully scanned: stopped at [secrets.decode] {} = {}",
+                    t.limit, t.value
+                ),
+                pattern: None,
+                rule: "decode-limit".to_string(),
+                provider: "decode".to_string(),
+                severity: Severity::Info,
+                preview: None,
+                fingerprint: None,
+            };
+            (t.path, finding)
+        }));
+        findings
+    }
+
+    fn match_pattern(&self, text: &str) -> Option<&CompiledDetector> {
+        self.rules.first_match(text).map(|i| &self.detectors[i])
+    }
+
+    /// Match a single line, returning the finding and the matched text
+    pub fn scan_line<'a>(&self, line_no: usize, line: &'a str) -> Option<(Finding, &'a str)> {
+        if has_ignore_marker(line) {
+            return None;
+        }
+
+        // Decodable JWTs are classified by their claims, wherever they appear
+        if let Some((token, decoded)) = jwt::find(line) {
+            let class = jwt::classify(&decoded, line);
+            let finding = Finding {
+                line: line_no,
+                message: class.message,
+                pattern: None,
+                rule: class.rule.to_string(),
+                provider: class.provider.to_string(),
+                severity: class.severity,
+                preview: Some(redact::preview(token)),
+                fingerprint: Some(redact::fingerprint(token)),
+            };
+            return Some((finding, token));
+        }
+
+        if is_env_reference(line) {
+            return None;
+        }
+        let index = self.rules.first_match(line)?;
+        let detector = &self.detectors[index];
+        let m = self.rules.regex(index).find(line)?;
+        let secret = &line[redact::value_span(line, m.range())];
+        let message = format!("Potential {}", detector.description);
+        let mut finding = Finding::from_detector(line_no, message, detector);
+        finding.preview = Some(redact::preview(secret));
+        finding.fingerprint = Some(redact::fingerprint(secret));
+        Some((finding, secret))
+    }
+
+    /// Byte ranges of every secret value in `text`, for redaction
+    pub fn secret_spans(&self, text: &str) -> Vec<Range<usize>> {
+        let detected = self.rules.matches(text).into_iter().flat_map(|i| {
+            self.rules
+                .regex(i)
+                .find_iter(text)
+                .map(|m| redact::value_span(text, m.range()))
+                .collect::<Vec<_>>()
+        });
+        redact::merge_spans(jwt::spans(text).into_iter().chain(detected).collect())
+    }
+
+    /// Run every per-line pass for one physical line
+    fn push_line(&self, state: &mut ScanState, line_no: usize, line: &str) {
+        if has_ignore_marker(line) {
+            state.ignored.insert(line_no);
+        }
+
+        let line_match = self
+            .scan_line(line_no, line)
+            .map(|(f, _)| f)
+            .or_else(|| self.scan_base64(line_no, line));
+        let matched = line_match.is_some();
+        if let Some(finding) = line_match {
+            state.add(finding);
+        }
+
+        if let Some(finding) = self.scan_pem_body(state, line_no, line) {
+            state.add(finding);
+        }
+
+        // Extend the current logical line or start a new one
+        let continued = state.span.last().is_some_and(|last| continues(last, line));
+        if !continued {
+            self.flush_span(state);
+            state.span_start = line_no;
+        }
+        state.span.push(line.to_string());
+        state.span_matched |= matched;
+    }
+
+    /// Run the line detectors over text hidden in base64 blobs
+    fn scan_base64(&self, line_no: usize, line: &str) -> Option<Finding> {
+        if has_ignore_marker(line) {
+            return None;
+        }
+        decode::base64_blobs(line).iter().find_map(|text| {
+            text.lines()
+                .find_map(|decoded| self.scan_line(line_no, decoded))
+                .map(|(mut finding, _)| {
+                    finding.message = format!("{} (base64-encoded)", finding.message);
+                    finding
+                })
+        })
+    }
+
+    /// Join continued lines and string concatenations into a logical line
+    fn flush_span(&self, state: &mut ScanState) {
+        let span = std::mem::take(&mut state.span);
+        let matched = std::mem::take(&mut state.span_matched);
+
+        // Single lines and spans with a single-line match are already reported
+        if span.len() < 2 || matched {
+            return;
+        }
+
+        let joined = span
+            .iter()
+            .map(|l| l.trim().trim_end_matches('\\'))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let joined = self.concat.replace_all(&joined, "");
+        if is_env_reference(&joined) {
+            return;
+        }
+
+        if let Some(d) = self.match_pattern(&joined) {
+            let start = state.span_start;
+            let end = start + span.len() - 1;
+            state.add(Finding::from_detector(
+                start,
+                format!(
+                    "Potential {} split across lines {}-{}",
+                    d.description, start, end
+                ),
+                d,
+            ));
+        }
+    }
+
+    /// Detect private key material inside PEM bodies, even without a header
+    fn scan_pem_body(&self, state: &mut ScanState, line_no: usize, line: &str) -> Option<Finding> {
+        if line.contains("-----BEGIN") {
+            state.in_pem_block = true;
+            return None;
+        }
+        if line.contains("-----END") {
+            state.in_pem_block = false;
+            return None;
+        }
+        // Blocks with a header are reported by the header pattern
+        if state.in_pem_block {
+            return None;
+        }
+
+        let body = strip_literal(line);
+        if body.len() < MIN_PEM_BODY_LEN || !is_private_key_material(&body) {
+            return None;
+        }
+        Some(Finding {
+            line: line_no,
+            message: "Private key material (PEM body without header)".to_string(),
+            pattern: None,
+            rule: "private-key-material".to_string(),
+            provider: "pem".to_string(),
+            severity: Severity::Critical,
+            preview: None,
+            fingerprint: Some(redact::fingerprint(&body)),
+        })
+    }
+
+    /// Tie sensitive key names to their values in JSON/TOML/YAML/.env files
+    fn scan_structured(&self, kind: StructuredKind, content: &str) -> Vec<Finding> {
+        let pairs = match kind {
+            StructuredKind::Json => match serde_json::from_str(content) {
+                Ok(value) => without_lines(json_pairs(&value)),
+                Err(_) => return vec![],
+            },
+            StructuredKind::Toml => match toml::from_str(content) {
+                Ok(value) => without_lines(toml_pairs(&value)),
+                Err(_) => return vec![],
+            },
+            StructuredKind::Yaml => without_lines(yaml_pairs(content)),
+            StructuredKind::Env => env_pairs(content)
+                .into_iter()
+                .map(|e| (e.key, e.value, Some(e.line)))
+                .collect(),
+        };
+
+        pairs
+            .into_iter()
+            .filter(|(key, value, _)| self.is_secret_assignment(key, value))
+            .map(|(key, value, line)| Finding {
+                line: line.unwrap_or_else(|| find_line(content, &key, &value)),
+                message: format!("Secret value assigned to '{}'", key),
+                pattern: None,
+                rule: "structured-secret-key".to_string(),
+                provider: "generic".to_string(),
+                severity: Severity::High,
+                preview: Some(redact::preview(&value)),
+                fingerprint: Some(redact::fingerprint(&value)),
+            })
+            .collect()
+    }
+
+    fn is_secret_assignment(&self, key: &str, value: &str) -> bool {
+        let name = key.rsplit('.').next().unwrap_or(key);
+        if name.starts_with("NEXT_PUBLIC_") || !self.sensitive_key.is_match(name) {
+            return false;
+        }
+        looks_like_secret(value) && !self.placeholder.is_match(value)
+    }
+}
+
+/// Per-file state while streaming lines through the scanner
+#[derive(Default)]
+struct ScanState {
+    findings: BTreeMap<usize, Finding>,
+    ignored: HashSet<usize>,
+    span: Vec<String>,
+    span_start: usize,
+    span_matched: bool,
+    in_pem_block: bool,
+}
+
+impl ScanState {
+    /// Keep the first finding per line, honouring ignore markers
+    fn add(&mut self, finding: Finding) {
+        if !self.ignored.contains(&finding.line) {
+            self.findings.entry(finding.line).or_insert(finding);
+        }
+    }
+}
+
+/// Structured file formats understood by the scanner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuredKind {
+    Json,
+    Toml,
+    Yaml,
+    Env,
+}
+
+impl StructuredKind {
+    pub fn from_path(path: &str) -> Option<Self> {
+        let name = Path::new(path).file_name()?.to_str()?;
+        if name.starts_with(".env") || name.ends_with(".env") {
+            return Some(Self::Env);
+        }
+        match Path::new(name).extension()?.to_str()? {
+            "json" | "webmanifest" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// A `KEY=value` entry from a dotenv file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvEntry {
+    pub key: String,
+    pub value: String,
+    pub line: usize,
+}
+
+/// Parse a dotenv file, including quoted values spanning several lines
+pub fn env_pairs(content: &str) -> Vec<EnvEntry> {
+    let mut entries = vec![];
+    let mut lines = content.lines().enumerate();
+
+    while let Some((i, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, raw)) = line.split_once('=') else {
+            continue;
+        };
+
+        let raw = raw.trim();
+        let value = match raw.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let rest = &raw[1..];
+                match rest.find(quote) {
+                    // Anything after the closing quote is a comment
+                    Some(end) => rest[..end].to_string(),
+                    None => {
+                        // Multi-line value, up to the line with the closing quote;
+                        // an unterminated quote only covers its own line
+                        let mut ahead = lines.clone();
+                        let mut value = rest.to_string();
+                        let mut closed = false;
+                        for (_, next) in ahead.by_ref() {
+                            value.push('\n');
+                            if let Some(end) = next.find(quote) {
+                                value.push_str(&next[..end]);
+                                closed = true;
+                                break;
+                            }
+                            value.push_str(next);
+                        }
+                        if closed {
+                            lines = ahead;
+                            value
+                        } else {
+                            rest.to_string()
+                        }
+                    }
+                }
+            }
+            _ => raw.split(" #").next().unwrap_or("").trim().to_string(),
+        };
+
+        entries.push(EnvEntry {
+            key: key.trim().to_string(),
+            value,
+            line: i + 1,
+        });
+    }
+
+    entries
+}
+
+fn json_pairs(value: &serde_json::Value) -> Vec<(String, String)> {
+    fn walk(value: &serde_json::Value, prefix: &str, out: &mut Vec<(String, String)>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (k, v) in map {
+                    walk(v, &join_key(prefix, k), out);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for v in items {
+                    walk(v, prefix, out);
+                }
+            }
+            serde_json::Value::String(s) => out.push((prefix.to_string(), s.clone())),
+            _ => {}
+        }
+    }
+
+    let mut out = vec![];
+    walk(value, "", &mut out);
+    out
+}
+
+fn toml_pairs(value: &toml::Value) -> Vec<(String, String)> {
+    fn walk(value: &toml::Value, prefix: &str, out: &mut Vec<(String, String)>) {
+        match value {
+            toml::Value::Table(table) => {
+                for (k, v) in table {
+                    walk(v, &join_key(prefix, k), out);
+                }
+            }
+            toml::Value::Array(items) => {
+                for v in items {
+                    walk(v, prefix, out);
+                }
+            }
+            toml::Value::String(s) => out.push((prefix.to_string(), s.clone())),
+            _ => {}
+        }
+    }
+
+    let mut out = vec![];
+    walk(value, "", &mut out);
+    out
+}
+
+/// Minimal YAML reader: nested mappings, list items and block scalars
+fn yaml_pairs(content: &str) -> Vec<(String, String)> {
+    let key_re =
+        Regex::new(r#"^(\s*)(?:-\s+)?("[^"]+"|'[^']+'|[A-Za-z0-9_.-]+)\s*:(?:\s+(.*))?$"#).unwrap();
+
+    let mut out = vec![];
+    let mut stack: Vec<(usize, String)> = vec![];
+    let lines: Vec<&str> = content.lines().collect();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let Some(caps) = key_re.captures(line) else {
+            continue;
+        };
+
+        let indent = caps[1].len();
+        let key = caps[2].trim_matches(['"', '\'']).to_string();
+        while stack.last().is_some_and(|(level, _)| *level >= indent) {
+            stack.pop();
+        }
+        let prefix = stack.last().map(|(_, k)| k.as_str()).unwrap_or("");
+        let full_key = join_key(prefix, &key);
+
+        let raw = caps.get(3).map(|m| m.as_str().trim()).unwrap_or("");
+        if raw.is_empty() {
+            stack.push((indent, full_key));
+            continue;
+        }
+
+        let value = if raw.starts_with('|') || raw.starts_with('>') {
+            let mut block = vec![];
+            while i < lines.len()
+                && (lines[i].trim().is_empty()
+                    || lines[i].len() - lines[i].trim_start().len() > indent)
+            {
+                block.push(lines[i].trim());
+                i += 1;
+            }
+            block.join("\n").trim().to_string()
+        } else if raw.starts_with('"') || raw.starts_with('\'') {
+            raw[1..].trim_end_matches(['"', '\'']).to_string()
+        } else {
+            raw.split(" #").next().unwrap_or("").trim().to_string()
+        };
+
+        out.push((full_key, value));
+    }
+
+    out
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Pairs from parsers that do not track source lines
+fn without_lines(pairs: Vec<(String, String)>) -> Vec<(String, String, Option<usize>)> {
+    pairs.into_iter().map(|(k, v)| (k, v, None)).collect()
+}
+
+/// Locate the line of a key/value pair
+///
+/// Takes the value occurrence closest after an occurrence of the key, so the
+/// same text earlier in the file (a comment, another key) is not reported.
+fn find_line(content: &str, key: &str, value: &str) -> usize {
+    let name = key.rsplit('.').next().unwrap_or(key);
+    let first_value_line = value.lines().next().unwrap_or(value);
+    let key_re = Regex::new(&format!(
+        r"(?:^|[^A-Za-z0-9_-]){}(?:[^A-Za-z0-9_-]|$)",
+        regex::escape(name)
+    ))
+    .unwrap();
+    let keys: Vec<usize> = key_re.find_iter(content).map(|m| m.start()).collect();
+
+    keys.iter()
+        .filter_map(|&k| content[k..].find(first_value_line).map(|v| (v, k + v)))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, offset)| offset)
+        .or_else(|| keys.first().copied())
+        .or_else(|| content.find(first_value_line))
+        .map(|offset| content[..offset].matches('\n').count() + 1)
+        .unwrap_or(1)
+}
+
+/// Lines that only reference environment variables are never secrets
+fn is_env_reference(line: &str) -> bool {
+    line.contains("process.env") || line.contains("NEXT_PUBLIC")
+}
+
+/// Whether `next` continues the logical line started by `line`
+fn continues(line: &str, next: &str) -> bool {
+    let line = line.trim_end();
+    let next = next.trim_start();
+    line.ends_with('+')
+        || line.ends_with('\\')
+        || line.ends_with(':')
+        || line.ends_with('=')
+        || next.starts_with('+')
+}
+
+/// Strip quotes, escaped newlines and separators around a literal
+fn strip_literal(line: &str) -> String {
+    line.trim()
+        .trim_end_matches(',')
+        .trim_end_matches('+')
+        .trim()
+        .trim_matches(['"', '\'', '`'])
+        .trim_end_matches("\\n")
+        .to_string()
+}
+
+/// Check whether a base64 line decodes to a private key structure
+fn is_private_key_material(body: &str) -> bool {
+    if !body
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/' || c == '=')
+    {
+        return false;
+    }
+
+    // "openssh-key-v1" magic
+    if body.starts_with("b3BlbnNzaC1rZXktdjE") {
+        return true;
+    }
+
+    // Decode whole 4-char groups only, the line may be one of many
+    let usable = body.len() - body.len() % 4;
+    let Ok(der) = STANDARD.decode(body[..usable].trim_end_matches('=')) else {
+        return false;
+    };
+
+    // PKCS#1 / PKCS#8: SEQUENCE { INTEGER 0, ... }
+    let pkcs = der.len() > 7 && der[0] == 0x30 && der[1] == 0x82 && der[4..7] == [0x02, 0x01, 0x00];
+    // SEC1 EC private key: SEQUENCE { INTEGER 1, OCTET STRING ... }
+    let sec1 = der.len() > 5 && der[0] == 0x30 && der[2..6] == [0x02, 0x01, 0x01, 0x04];
+    pkcs || sec1
+}
+
+/// Reject values that cannot plausibly be a credential
+fn looks_like_secret(value: &str) -> bool {
+    if value.len() < 8 || value.contains(char::is_whitespace) {
+        return false;
+    }
+    if value.starts_with("https://") || value.starts_with("http://") {
+        return value.split('/').count() > 4;
+    }
+    let has_digit = value.chars().any(|c| c.is_ascii_digit());
+    let has_upper = value.chars().any(|c| c.is_ascii_uppercase());
+    let has_lower = value.chars().any(|c| c.is_ascii_lowercase());
+    has_digit || (has_upper && has_lower)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanner() -> SecretScanner {
+        SecretScanner::new(&SecretsConfig::default())
+    }
+
+    #[test]
+    fn test_base64_encoded_secret() {
+        let content = "const blob = \"R0lUSFVCX1RPS0VOPWdocF9hQmNEZUZnSGlKa0xtTm9QcVJzVHVWd1h5WjAxMjM0NTY3ODk=\";\n";
+        let findings = scanner().scan("lib/client.ts", content);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule, "github-pat");
+        assert!(findings[0].message.ends_with("(base64-encoded)"));
+    }
+
+    #[test]
+    fn test_concatenated_secret() {
+        let content = "const key = \"sbp_0123456789abcdef\" +\n  \"0123456789abcdef0123\";\n";
+        let findings = scanner().scan("lib/client.ts", content);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 1);
+    }
+
+    #[test]
+    fn test_value_on_next_line() {
+        let content = "const config = {\n  api_key:\n    'abc123def456',\n};\n";
+        let findings = scanner().scan("lib/client.ts", content);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 2);
+    }
+
+    #[test]
+    fn test_structured_json_key() {
+        let content = r#"{
+  "supabase": {
+    "url": "https://abc.supabase.co",
+    "service_role_key": "Zm9vYmFyYmF6cXV4MTIzNDU2"
+  }
+}"#;
+        let findings = scanner().scan("config/local.json", content);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 4);
+        assert!(findings[0].message.contains("supabase.service_role_key"));
+
+        // The same text earlier in the file is not the assignment
+        let yaml = "# rotated: Zm9vYmFyYmF6cXV4MTIzNDU2\nfixture: Zm9vYmFyYmF6cXV4MTIzNDU2\nauth:\n  api_key: Zm9vYmFyYmF6cXV4MTIzNDU2\n";
+        let findings = scanner().scan("config/local.yml", yaml);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 4);
+    }
+
+    #[test]
+    fn test_structured_placeholders_ignored() {
+        let env =
+            "SUPABASE_SERVICE_ROLE_KEY=your-service-role-key\nCRON_SECRET=\"${CRON_SECRET}\"\n";
+        assert!(scanner().scan(".env.local", env).is_empty());
+
+        let yaml = "env:\n  CRON_SECRET: ${{ secrets.CRON_SECRET }}\n";
+        assert!(scanner().scan("deploy.yml", yaml).is_empty());
+
+        // JWTs are classified by their decoded claims; a bare HS256 header is not one
+        let truncated = "SUPABASE_SERVICE_ROLE_KEY=eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...\n";
+        assert!(scanner().scan(".env.local.example", truncated).is_empty());
+    }
+
+    #[test]
+    fn test_env_multiline_value() {
+        let entries = env_pairs("A=1\nKEY=\"line1\nline2\"\nB='x' \n");
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1].value, "line1\nline2");
+        assert_eq!(entries[2].line, 4);
+
+        // A comment after the closing quote, and an unterminated quote
+        let entries = env_pairs("A=\"abc\" # note \"x\"\nB=\"\nC=3\n");
+        let pairs: Vec<(&str, &str)> = entries
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![("A", "abc"), ("B", ""), ("C", "3")]);
+        assert_eq!(entries[2].line, 3);
+    }
+
+    #[test]
+    fn test_pem_body_without_header() {
+        // PKCS#8 prefix: SEQUENCE, INTEGER 0, AlgorithmIdentifier
+        let content =
+            "const k = [\n  \"MIIEvQIBADANBgkqhkiG9w0BAQEFAASCBKcwggSjAgEAAoIBAQC7\",\n];\n";
+        let findings = scanner().scan("lib/keys.ts", content);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 2);
+
+        // Certificates are public
+        let cert = "\"MIIDdzCCAl+gAwIBAgIEAgAAuTANBgkqhkiG9w0BAQUFADBaMQswCQYD\"\n";
+        assert!(scanner().scan("lib/keys.ts", cert).is_empty());
+    }
+}
@@ -0,0 +1,402 @@
+use crate::config::SignaturesConfig;
+use crate::{CheckResult, Violation};
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const COMMIT_SEPARATOR: char = '\x1e';
+const FIELD_SEPARATOR: char = '\x1f';
+
+/// Key type marking GPG fingerprints in the allowed-signers file
+const GPG_KEY_TYPE: &str = "gpg";
+
+/// Keys allowed to sign commits
+///
+/// The file uses ssh-keygen's allowed-signers format. GPG keys are listed as
+/// `<principal> gpg <FINGERPRINT>`; those lines are left out of the copy
+/// handed to git, which would otherwise warn about them.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AllowedSigners {
+    ssh: Vec<String>,
+    gpg: Vec<String>,
+}
+
+impl AllowedSigners {
+    pub fn parse(text: &str) -> Self {
+        let mut signers = Self::default();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                [_, kind, fingerprint, ..] if *kind == GPG_KEY_TYPE => {
+                    signers.gpg.push(fingerprint.to_uppercase());
+                }
+                _ => signers.ssh.push(line.to_string()),
+            }
+        }
+        signers
+    }
+
+    fn allows_gpg(&self, fingerprint: &str) -> bool {
+        !fingerprint.is_empty() && self.gpg.iter().any(|f| f == &fingerprint.to_uppercase())
+    }
+}
+
+/// Signature details of one commit from `git log`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SignedCommit {
+    sha: String,
+    /// `%G?`: G good, U good but unknown validity, N none, B bad, E can't check, ...
+    status: char,
+    fingerprint: String,
+    primary_fingerprint: String,
+    signer: String,
+    author: String,
+    date: String,
+    subject: String,
+}
+
+fn parse_log(output: &str) -> Vec<SignedCommit> {
+    output
+        .split(COMMIT_SEPARATOR)
+        .filter_map(|record| {
+            let fields: Vec<&str> = record
+                .trim_end_matches('\n')
+                .split(FIELD_SEPARATOR)
+                .collect();
+            let [sha, status, fingerprint, primary, signer, author, date, subject] =
+                fields.as_slice()
+            else {
+                return None;
+            };
+            Some(SignedCommit {
+                sha: sha.to_string(),
+                status: status.chars().next().unwrap_or('N'),
+                fingerprint: fingerprint.to_string(),
+                primary_fingerprint: primary.to_string(),
+                signer: signer.to_string(),
+                author: author.to_string(),
+                date: date.to_string(),
+                subject: subject.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Rule and message for a commit that is not signed by an allowed key
+///
+/// `verified` is the outcome of `git verify-commit`, run for good signatures.
+fn classify(
+    commit: &SignedCommit,
+    signers: &AllowedSigners,
+    verified: bool,
+) -> Option<(&'static str, String)> {
+    let key = if commit.fingerprint.is_empty() {
+        "an unknown key".to_string()
+    } else {
+        format!("key {}", commit.fingerprint)
+    };
+    match commit.status {
+        'N' => Some(("unsigned-commit", "Commit is not signed".to_string())),
+        'B' => Some(("bad-signature", format!("Bad signature from {}", key))),
+        'X' => Some(("expired-signature", "Signature has expired".to_string())),
+        'Y' => Some(("expired-signature", format!("Signed by expired {}", key))),
+        'R' => Some(("revoked-key", format!("Signed by revoked {}", key))),
+        'G' | 'U' => {
+            let ssh = commit.fingerprint.starts_with("SHA256:");
+            // git only reports G for SSH keys that match an allowed principal
+            let allowed = if ssh {
+                commit.status == 'G'
+            } else {
+                signers.allows_gpg(&commit.fingerprint)
+                    || signers.allows_gpg(&commit.primary_fingerprint)
+            };
+            if !allowed {
+                Some((
+                    "unknown-signer",
+                    format!("Signed by {} not in the allowed signers", key),
+                ))
+            } else if !verified {
+                Some((
+                    "bad-signature",
+                    "git verify-commit rejected the signature".to_string(),
+                ))
+            } else {
+                None
+            }
+        }
+        _ => Some((
+            "unknown-signer",
+            format!("Cannot check signature by {}", key),
+        )),
+    }
+}
+
+/// Temporary SSH-only allowed-signers file passed to git
+struct SignersFile(PathBuf);
+
+impl SignersFile {
+    fn create(signers: &AllowedSigners) -> Result<Self> {
+        let path =
+            std::env::temp_dir().join(format!("audit-allowed-signers-{}", std::process::id()));
+        let mut text = signers.ssh.join("\n");
+        text.push('\n');
+        fs::write(&path, text).context("Failed to write temporary allowed signers file")?;
+        Ok(Self(path))
+    }
+
+    fn git_option(&self) -> String {
+        format!("gpg.ssh.allowedSignersFile={}", self.0.display())
+    }
+}
+
+impl Drop for SignersFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Commits in `range` that are not signed by an allowed key, with rule and message
+fn verify_range(
+    repo: &Path,
+    range: &str,
+    signers: &AllowedSigners,
+) -> Result<Vec<(SignedCommit, &'static str, String)>> {
+    let file = SignersFile::create(signers)?;
+    let option = file.git_option();
+    let format = format!(
+        "--format={}%H{f}%G?{f}%GF{f}%GP{f}%GS{f}%an <%ae>{f}%aI{f}%s",
+        COMMIT_SEPARATOR,
+        f = FIELD_SEPARATOR
+    );
+
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args([
+            "-c",
+            &option,
+            "log",
+            "--reverse",
+            "--no-color",
+            &format,
+            range,
+        ])
+        .output()
+        .context("Failed to run git log")?;
+    if !output.status.success() {
+        bail!(
+            "git log failed for range {}: {}",
+            range,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let mut issues = vec![];
+    for commit in parse_log(&String::from_utf8_lossy(&output.stdout)) {
+        let verified = matches!(commit.status, 'G' | 'U')
+            && Command::new("git")
+                .current_dir(repo)
+                .args(["-c", &option, "verify-commit", &commit.sha])
+                .output()
+                .is_ok_and(|o| o.status.success());
+        if let Some((rule, message)) = classify(&commit, signers, verified) {
+            issues.push((commit, rule, message));
+        }
+    }
+    Ok(issues)
+}
+
+/// Check that every commit in `range` is signed by a key in the allowed-signers file
+pub fn check_signatures(range: &str, config: &SignaturesConfig) -> Result<CheckResult> {
+    let mut result = CheckResult::new("verify-signatures");
+
+    let text = fs::read_to_string(&config.allowed_signers).with_context(|| {
+        format!(
+            "Failed to read allowed signers file {} ([signatures].allowed_signers)",
+            config.allowed_signers
+        )
+    })?;
+    let signers = AllowedSigners::parse(&text);
+    let range = if range.contains("..") {
+        range.to_string()
+    } else {
+        format!("{}..HEAD", range)
+    };
+
+    let issues = verify_range(Path::new("."), &range, &signers)?;
+    for (commit, rule, message) in issues {
+        let short = &commit.sha[..commit.sha.len().min(12)];
+        println!("{} {} {}", "❌".red(), short.yellow(), commit.subject);
+        println!("   {}: {}", rule, message);
+        if !commit.signer.is_empty() {
+            println!("   Signer: {}", commit.signer);
+        }
+        result.add_violation(Violation {
+            file: short.to_string(),
+            line: None,
+            message,
+            rule: Some(rule.to_string()),
+            commit: Some(commit.sha.clone()),
+            author: Some(commit.author),
+            date: Some(commit.date),
+            ..Default::default()
+        });
+    }
+
+    if result.passed {
+        println!(
+            "{}",
+            format!("✅ All commits in {} are signed by allowed keys", range).green()
+        );
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(repo: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(repo)
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn keygen(dir: &Path, name: &str) -> String {
+        let key = dir.join(name);
+        Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", name, "-f"])
+            .arg(&key)
+            .output()
+            .unwrap();
+        let public = fs::read_to_string(key.with_extension("pub")).unwrap();
+        public
+            .split_whitespace()
+            .take(2)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_classify_gpg_signatures() {
+        let signers = AllowedSigners::parse(
+            "# maintainers\ndev@example.com gpg bf6de8622c09584f1f4d168030d60bbee07c90c2\ndev@example.com namespaces=\"git\" ssh-ed25519 AAAA\n",
+        );
+        assert_eq!(signers.ssh.len(), 1);
+        let mut commit = SignedCommit {
+            sha: "abc".to_string(),
+            status: 'U',
+            fingerprint: "BF6DE8622C09584F1F4D168030D60BBEE07C90C2".to_string(),
+            primary_fingerprint: String::new(),
+            signer: "Dev <dev@example.com>".to_string(),
+            author: String::new(),
+            date: String::new(),
+            subject: String::new(),
+        };
+        assert_eq!(classify(&commit, &signers, true), None);
+        assert_eq!(
+            classify(&commit, &signers, false).unwrap().0,
+            "bad-signature"
+        );
+        commit.fingerprint = "0123456789ABCDEF0123456789ABCDEF01234567".to_string();
+        assert_eq!(
+            classify(&commit, &signers, true).unwrap().0,
+            "unknown-signer"
+        );
+        commit.status = 'E';
+        assert_eq!(
+            classify(&commit, &signers, false).unwrap().0,
+            "unknown-signer"
+        );
+        commit.status = 'R';
+        assert_eq!(classify(&commit, &signers, false).unwrap().0, "revoked-key");
+    }
+
+    #[test]
+    fn test_verify_range_with_ssh_keys() {
+        if Command::new("ssh-keygen").arg("-?").output().is_err() {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("audit-signatures-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let repo = dir.join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        let allowed = keygen(&dir, "dev");
+        keygen(&dir, "stranger");
+
+        git(&repo, &["init", "-q"]);
+        for (key, value) in [
+            ("user.name", "Dev"),
+            ("user.email", "dev@example.com"),
+            ("gpg.format", "ssh"),
+            ("commit.gpgsign", "false"),
+        ] {
+            git(&repo, &["config", key, value]);
+        }
+        let key = |name: &str| {
+            format!(
+                "user.signingkey={}",
+                dir.join(format!("{}.pub", name)).display()
+            )
+        };
+        git(
+            &repo,
+            &["commit", "-q", "--allow-empty", "-m", "chore: init"],
+        );
+        git(
+            &repo,
+            &[
+                "-c",
+                &key("dev"),
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-S",
+                "-m",
+                "feat: signed",
+            ],
+        );
+        git(
+            &repo,
+            &[
+                "-c",
+                &key("stranger"),
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-S",
+                "-m",
+                "feat: stranger",
+            ],
+        );
+        git(
+            &repo,
+            &["commit", "-q", "--allow-empty", "-m", "feat: unsigned"],
+        );
+
+        let signers =
+            AllowedSigners::parse(&format!("dev@example.com namespaces=\"git\" {}\n", allowed));
+        let issues = verify_range(&repo, "HEAD~3..HEAD", &signers).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            issues
+                .iter()
+                .map(|(c, rule, _)| (c.subject.as_str(), *rule))
+                .collect::<Vec<_>>(),
+            vec![
+                ("feat: stranger", "unknown-signer"),
+                ("feat: unsigned", "unsigned-commit")
+            ]
+        );
+    }
+}